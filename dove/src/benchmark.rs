@@ -1,25 +1,21 @@
-use pijersi::position::Position;
+use pijersi::epd::parse_records;
 use std::time::{Duration, Instant};
 use ugi::go::GoSettings;
 
 use crate::random;
 
 pub fn benchmark() {
-    let positions = [
-        "startpos",
-        "srp-r-s-2/p-1s-1r-1p-/4p-1/1RPw-3sr/2w-1RSP-/P-2WWS-2/R-S-S-1PR1 w 0 1",
-        "1p-sr1p-r-/p-r-1w-w-s-p-/4r-1/3ss3/SPR-S-2R-/P-2WW2P-/R-2R-SPS- w 0 1",
-    ];
+    let records = parse_records(include_str!("benchmark.epd")).unwrap();
 
     println!(
-        "{:<3}  {:>10}  {:>12}  {:>10}  {:>10}  {:>6}  {:>7}   {}",
-        "Pos", "Nodes", "ΣNodes", "NPS", "ΣNPS", "Time", "ΣTime", "FEN"
+        "{:<3}  {:>10}  {:>12}  {:>10}  {:>10}  {:>6}  {:>7}   FEN",
+        "Pos", "Nodes", "ΣNodes", "NPS", "ΣNPS", "Time", "ΣTime"
     );
 
     let mut total_nodes = 0;
     let mut total_time = Duration::default();
-    for (idx, fen) in positions.iter().enumerate() {
-        let pos = Position::from_fen(fen);
+    for (idx, record) in records.iter().enumerate() {
+        let pos = record.position().unwrap();
         let t0 = Instant::now();
         let result = random::root::root(&pos, &GoSettings::from_depth(6));
         let elapsed = t0.elapsed();
@@ -46,7 +42,7 @@ pub fn benchmark() {
             total_nps,
             elapsed.as_secs_f32(),
             total_time.as_secs_f32(),
-            fen
+            record.fen
         );
     }
 
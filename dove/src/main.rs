@@ -29,7 +29,7 @@ fn main() {
                 println!("Debug enabled");
             }
         }
-        "quit" => return,
+        "quit" => (),
         _ => println!("Unknown protocol"),
-    };
+    }
 }
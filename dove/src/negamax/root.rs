@@ -9,13 +9,8 @@ const MATE_SCORE: i32 = 100_000_000;
 
 #[must_use]
 pub fn eval(pos: &Position) -> i32 {
-    let us_lower = pos.get_lower() & pos.get_us();
-    let us_upper = pos.get_upper() & pos.get_us();
-    let num_us = us_lower.count() + us_upper.count();
-
-    let them_lower = pos.get_lower() & pos.get_them();
-    let them_upper = pos.get_upper() & pos.get_them();
-    let num_them = them_lower.count() + them_upper.count();
+    let num_us = pos.num_pieces(pos.turn);
+    let num_them = pos.num_pieces(!pos.turn);
 
     100 * (num_us - num_them)
 }
@@ -57,14 +52,14 @@ pub fn negamax(
         return eval(pos);
     }
 
-    if should_stop(&stats) {
+    if should_stop(stats) {
         return 0;
     }
 
     let mut best_score = -INF_SCORE;
     let mut best_move = None;
 
-    pos.move_generator(|mv| {
+    for mv in pos.moves() {
         let npos = pos.after_move(&mv);
         let score = -negamax(should_stop, stats, &npos, depth - 1, ply + 1);
 
@@ -72,9 +67,7 @@ pub fn negamax(
             best_score = score;
             best_move = Some(mv);
         }
-
-        false
-    });
+    }
 
     if is_root {
         stats.bestmove = best_move;
@@ -89,7 +82,7 @@ pub fn root(pos: &Position, settings: &GoSettings) -> SearchResult {
     let mut bestmove = None;
 
     let should_stop = |stats: &SearchStats| -> bool {
-        if let Some(_) = settings.depth {
+        if settings.depth.is_some() {
             return false;
         }
 
@@ -106,11 +99,7 @@ pub fn root(pos: &Position, settings: &GoSettings) -> SearchResult {
         false
     };
 
-    let max_depth = if let Some(depth) = settings.depth {
-        depth
-    } else {
-        128
-    };
+    let max_depth = settings.depth.unwrap_or(128);
 
     for i in 1..=max_depth {
         let score = negamax(&should_stop, &mut stats, pos, i, 0);
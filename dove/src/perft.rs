@@ -1,7 +1,7 @@
 use pijersi::position::Position;
 use std::time::Instant;
 
-pub fn perft(pos: &Position, depth: i32) -> () {
+pub fn perft(pos: &Position, depth: i32) {
     let t0 = Instant::now();
     for i in 0..=depth {
         let nodes = pos.perft(i);
@@ -7,7 +7,7 @@ use ugi::{
 };
 
 impl UGI for EngineState {
-    fn init(&mut self) -> () {
+    fn init(&mut self) {
         if self.debug.value {
             println!("info string init begin");
         }
@@ -19,7 +19,7 @@ impl UGI for EngineState {
         self.uginewgame();
     }
 
-    fn shutdown(&mut self) -> () {
+    fn shutdown(&mut self) {
         if self.debug.value {
             println!("info string shutdown");
         }
@@ -33,7 +33,7 @@ impl UGI for EngineState {
         "kz04px"
     }
 
-    fn uginewgame(&mut self) -> () {
+    fn uginewgame(&mut self) {
         if self.debug.value {
             println!("info string new game");
         }
@@ -50,11 +50,11 @@ impl UGI for EngineState {
         }
     }
 
-    fn isready(&mut self) -> () {
+    fn isready(&mut self) {
         println!("readyok");
     }
 
-    fn position(&mut self, fen: &str) -> () {
+    fn position(&mut self, fen: &str) {
         if self.debug.value {
             println!("info string set fen '{}'", fen);
         }
@@ -62,11 +62,16 @@ impl UGI for EngineState {
         self.pos.set_fen(fen);
     }
 
-    fn moves(&mut self, movestr: &str) -> () {
+    fn moves(&mut self, movestr: &str) {
         if self.debug.value {
             println!("info string apply move '{}'", movestr);
         }
 
+        if let Err(reason) = self.pos.check_move_str(movestr) {
+            println!("info string illegal move '{}': {}", movestr, reason);
+            return;
+        }
+
         let moves = self.pos.legal_moves();
         for mv in moves {
             if mv.to_string() == movestr {
@@ -76,7 +81,7 @@ impl UGI for EngineState {
         }
     }
 
-    fn go(&mut self, settings: &GoSettings) -> () {
+    fn go(&mut self, settings: &GoSettings) {
         if self.debug.value {
             println!("info string go {}", settings);
         }
@@ -85,7 +90,7 @@ impl UGI for EngineState {
             GoKind::Search => {
                 match self.search.value.as_str() {
                     "random" => {
-                        let result = random::root::root(&self.pos, &settings);
+                        let result = random::root::root(&self.pos, settings);
 
                         match (result.bestmove, result.ponder) {
                             (Some(best), Some(ponder)) => {
@@ -97,7 +102,7 @@ impl UGI for EngineState {
                         }
                     }
                     "negamax" => {
-                        let result = negamax::root::root(&self.pos, &settings);
+                        let result = negamax::root::root(&self.pos, settings);
 
                         match (result.bestmove, result.ponder) {
                             (Some(best), Some(ponder)) => {
@@ -109,7 +114,7 @@ impl UGI for EngineState {
                         }
                     }
                     "alphabeta" => {
-                        let result = alphabeta::root::root(&self.pos, &settings);
+                        let result = alphabeta::root::root(&self.pos, settings);
 
                         match (result.bestmove, result.ponder) {
                             (Some(best), Some(ponder)) => {
@@ -136,22 +141,22 @@ impl UGI for EngineState {
         }
     }
 
-    fn stop(&mut self) -> () {
+    fn stop(&mut self) {
         if self.debug.value {
             println!("info string stop");
         }
     }
 
-    fn print(&self) -> () {
+    fn print(&self) {
         print!("{}", self.pos);
     }
 
-    fn print_options(&self) -> () {
+    fn print_options(&self) {
         println!("{}", self.debug);
         println!("{}", self.search);
     }
 
-    fn set_option(&mut self, name: &str, value: &str) -> () {
+    fn set_option(&mut self, name: &str, value: &str) {
         if self.debug.value {
             println!("info string set option '{}' to '{}'", name, value);
         }
@@ -172,21 +177,21 @@ impl UGI for EngineState {
     }
 
     // Queries
-    fn query_p1turn(&self) -> () {
+    fn query_p1turn(&self) {
         match self.pos.turn {
             Side::White => println!("response true"),
             Side::Black => println!("response false"),
         }
     }
 
-    fn query_gameover(&self) -> () {
+    fn query_gameover(&self) {
         match self.pos.is_gameover() {
             true => println!("response true"),
             false => println!("response false"),
         }
     }
 
-    fn query_result(&self) -> () {
+    fn query_result(&self) {
         match self.pos.result() {
             Some(GameResult::WhiteWin) => println!("response p1win"),
             Some(GameResult::BlackWin) => println!("response p2win"),
@@ -1,6 +1,6 @@
 use pijersi::position::Position;
 
-pub fn split(pos: &Position, depth: i32) -> () {
+pub fn split(pos: &Position, depth: i32) {
     let moves = pos.legal_moves();
     let mut total_nodes = 0;
 
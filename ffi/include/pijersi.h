@@ -0,0 +1,127 @@
+/*
+ * C interface to the pijersi rules engine.
+ *
+ * Generated from ffi/src/lib.rs by cbindgen, don't edit it by hand. Rerun
+ * UPDATE_HEADER=1 cargo test -p pijersi-ffi --test header instead.
+ *
+ * Positions are created with pj_position_new and released with
+ * pj_position_free. Every PjPosition pointer passed in must come from
+ * pj_position_new and not have been freed, or be NULL, in which case the
+ * function returns its error value. String arguments must be NUL terminated.
+ *
+ * Functions that produce strings take a buffer and its size. They write as
+ * much as fits followed by a NUL and return the full length without the NUL,
+ * so a return value >= len means the output was truncated. Passing a NULL
+ * buffer or a size of 0 only returns the length.
+ */
+
+#ifndef PIJERSI_H
+#define PIJERSI_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* Squares on the board pijersi was built for, 45 on the standard one */
+#define PJ_NUM_SQUARES 45
+
+#define PJ_OK 0
+
+#define PJ_ERROR -1
+
+#define PJ_WHITE 0
+
+#define PJ_BLACK 1
+
+#define PJ_NONE -1
+
+#define PJ_ROCK 0
+
+#define PJ_PAPER 1
+
+#define PJ_SCISSORS 2
+
+#define PJ_WISE 3
+
+#define PJ_ONGOING 0
+
+#define PJ_WHITE_WIN 1
+
+#define PJ_BLACK_WIN 2
+
+#define PJ_DRAW 3
+
+/**
+ * Created with pj_position_new and released with pj_position_free
+ */
+typedef struct PjPosition PjPosition;
+
+/**
+ * side is PJ_WHITE or PJ_BLACK, top is PJ_NONE for a single piece
+ */
+typedef struct {
+  int side;
+  int bottom;
+  int top;
+} PjStack;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The start position
+ */
+PjPosition *pj_position_new(void);
+
+void pj_position_free(PjPosition *pos);
+
+/**
+ * PJ_OK, or PJ_ERROR if the FEN is invalid and the position is unchanged. The
+ * position has to be valid as well as well formed, so a FEN with more pieces
+ * than a side starts with is refused even though the Rust Position::set_fen
+ * would load it.
+ */
+int pj_position_set_fen(PjPosition *pos, const char *fen);
+
+size_t pj_position_get_fen(const PjPosition *pos, char *buf, size_t len);
+
+/**
+ * PJ_WHITE or PJ_BLACK
+ */
+int pj_position_turn(const PjPosition *pos);
+
+int pj_position_num_legal_moves(const PjPosition *pos);
+
+/**
+ * Space separated moves such as "a1a2 a1a2b2"
+ */
+size_t pj_position_legal_moves(const PjPosition *pos, char *buf, size_t len);
+
+/**
+ * PJ_OK, or PJ_ERROR if the move isn't legal and the position is unchanged
+ */
+int pj_position_make_move(PjPosition *pos, const char *mv);
+
+/**
+ * One of PJ_ONGOING, PJ_WHITE_WIN, PJ_BLACK_WIN or PJ_DRAW
+ */
+int pj_position_result(const PjPosition *pos);
+
+uint64_t pj_position_perft(const PjPosition *pos, int depth);
+
+/**
+ * Squares are 0 to PJ_NUM_SQUARES - 1. Returns 1 and fills in out if the
+ * square is occupied, 0 if it's empty, PJ_ERROR for a bad square.
+ */
+int pj_position_stack(const PjPosition *pos, int square, PjStack *out);
+
+/**
+ * Square index for a name such as "d4", PJ_ERROR if there's no such square
+ */
+int pj_square_from_name(const char *name);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PIJERSI_H */
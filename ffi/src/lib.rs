@@ -0,0 +1,304 @@
+// C bindings for the rules crate. include/pijersi.h is generated from this
+// file by cbindgen, run UPDATE_HEADER=1 cargo test -p pijersi-ffi --test header
+// after changing it. The /// comments end up in the header. Pointer arguments
+// follow the contract written at the top of it, which is why the individual
+// functions don't repeat it.
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    ffi::{c_char, c_int, CStr},
+    ptr,
+};
+
+use pijersi::{
+    geometry::NUM_SQUARES,
+    layer::Layer,
+    position::{Piece, Position},
+    result::GameResult,
+    side::Side,
+    square::Square,
+};
+
+pub const PJ_OK: c_int = 0;
+pub const PJ_ERROR: c_int = -1;
+
+pub const PJ_WHITE: c_int = 0;
+pub const PJ_BLACK: c_int = 1;
+
+pub const PJ_NONE: c_int = -1;
+pub const PJ_ROCK: c_int = 0;
+pub const PJ_PAPER: c_int = 1;
+pub const PJ_SCISSORS: c_int = 2;
+pub const PJ_WISE: c_int = 3;
+
+pub const PJ_ONGOING: c_int = 0;
+pub const PJ_WHITE_WIN: c_int = 1;
+pub const PJ_BLACK_WIN: c_int = 2;
+pub const PJ_DRAW: c_int = 3;
+
+pub const PJ_NUM_SQUARES: c_int = NUM_SQUARES as c_int;
+
+/// Created with pj_position_new and released with pj_position_free
+pub struct PjPosition {
+    pos: Position,
+}
+
+/// side is PJ_WHITE or PJ_BLACK, top is PJ_NONE for a single piece
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PjStack {
+    pub side: c_int,
+    pub bottom: c_int,
+    pub top: c_int,
+}
+
+fn side_code(side: Side) -> c_int {
+    match side {
+        Side::White => PJ_WHITE,
+        Side::Black => PJ_BLACK,
+    }
+}
+
+fn piece_code(piece: Option<Piece>) -> c_int {
+    match piece {
+        None => PJ_NONE,
+        Some(Piece::Rock) => PJ_ROCK,
+        Some(Piece::Paper) => PJ_PAPER,
+        Some(Piece::Scissors) => PJ_SCISSORS,
+        Some(Piece::Wise) => PJ_WISE,
+    }
+}
+
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        None
+    } else {
+        CStr::from_ptr(s).to_str().ok()
+    }
+}
+
+// Copies as much of the string as fits plus a NUL, returns the full length
+unsafe fn write_str(s: &str, buf: *mut c_char, len: usize) -> usize {
+    if !buf.is_null() && len > 0 {
+        let n = s.len().min(len - 1);
+        ptr::copy_nonoverlapping(s.as_ptr() as *const c_char, buf, n);
+        *buf.add(n) = 0;
+    }
+    s.len()
+}
+
+/// The start position
+#[no_mangle]
+pub extern "C" fn pj_position_new() -> *mut PjPosition {
+    Box::into_raw(Box::new(PjPosition {
+        pos: Position::from_fen("startpos"),
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_free(pos: *mut PjPosition) {
+    if !pos.is_null() {
+        drop(Box::from_raw(pos));
+    }
+}
+
+/// PJ_OK, or PJ_ERROR if the FEN is invalid and the position is unchanged. The
+/// position has to be valid as well as well formed, so a FEN with more pieces
+/// than a side starts with is refused even though the Rust Position::set_fen
+/// would load it.
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_set_fen(pos: *mut PjPosition, fen: *const c_char) -> c_int {
+    let (Some(pos), Some(fen)) = (pos.as_mut(), str_arg(fen)) else {
+        return PJ_ERROR;
+    };
+
+    match Position::parse_fen(fen) {
+        Ok(new) => {
+            pos.pos = new;
+            PJ_OK
+        }
+        Err(_) => PJ_ERROR,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_get_fen(
+    pos: *const PjPosition,
+    buf: *mut c_char,
+    len: usize,
+) -> usize {
+    match pos.as_ref() {
+        Some(pos) => write_str(&pos.pos.get_fen(), buf, len),
+        None => write_str("", buf, len),
+    }
+}
+
+/// PJ_WHITE or PJ_BLACK
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_turn(pos: *const PjPosition) -> c_int {
+    match pos.as_ref() {
+        Some(pos) => side_code(pos.pos.turn),
+        None => PJ_ERROR,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_num_legal_moves(pos: *const PjPosition) -> c_int {
+    match pos.as_ref() {
+        Some(pos) => pos.pos.legal_moves().len() as c_int,
+        None => PJ_ERROR,
+    }
+}
+
+/// Space separated moves such as "a1a2 a1a2b2"
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_legal_moves(
+    pos: *const PjPosition,
+    buf: *mut c_char,
+    len: usize,
+) -> usize {
+    let moves = match pos.as_ref() {
+        Some(pos) => pos
+            .pos
+            .moves()
+            .map(|mv| mv.to_string())
+            .collect::<Vec<String>>()
+            .join(" "),
+        None => String::new(),
+    };
+
+    write_str(&moves, buf, len)
+}
+
+/// PJ_OK, or PJ_ERROR if the move isn't legal and the position is unchanged
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_make_move(pos: *mut PjPosition, mv: *const c_char) -> c_int {
+    let (Some(pos), Some(mv)) = (pos.as_mut(), str_arg(mv)) else {
+        return PJ_ERROR;
+    };
+
+    match pos.pos.moves().find(|legal| legal.to_string() == mv) {
+        Some(legal) => {
+            pos.pos.makemove(&legal);
+            PJ_OK
+        }
+        None => PJ_ERROR,
+    }
+}
+
+/// One of PJ_ONGOING, PJ_WHITE_WIN, PJ_BLACK_WIN or PJ_DRAW
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_result(pos: *const PjPosition) -> c_int {
+    match pos.as_ref().map(|pos| pos.pos.result()) {
+        Some(None) => PJ_ONGOING,
+        Some(Some(GameResult::WhiteWin)) => PJ_WHITE_WIN,
+        Some(Some(GameResult::BlackWin)) => PJ_BLACK_WIN,
+        Some(Some(GameResult::Draw)) => PJ_DRAW,
+        None => PJ_ERROR,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_perft(pos: *const PjPosition, depth: c_int) -> u64 {
+    match pos.as_ref() {
+        Some(pos) => pos.pos.perft(depth),
+        None => 0,
+    }
+}
+
+/// Squares are 0 to PJ_NUM_SQUARES - 1. Returns 1 and fills in out if the
+/// square is occupied, 0 if it's empty, PJ_ERROR for a bad square.
+#[no_mangle]
+pub unsafe extern "C" fn pj_position_stack(
+    pos: *const PjPosition,
+    square: c_int,
+    out: *mut PjStack,
+) -> c_int {
+    let Some(pos) = pos.as_ref() else {
+        return PJ_ERROR;
+    };
+    if !(0..PJ_NUM_SQUARES).contains(&square) {
+        return PJ_ERROR;
+    }
+
+    let sq = Square(square as u8);
+    match pos.pos.get_side_on(sq) {
+        Some(side) => {
+            if let Some(out) = out.as_mut() {
+                *out = PjStack {
+                    side: side_code(side),
+                    bottom: piece_code(pos.pos.get_piece_on(Layer::Lower, sq)),
+                    top: piece_code(pos.pos.get_piece_on(Layer::Upper, sq)),
+                };
+            }
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Square index for a name such as "d4", PJ_ERROR if there's no such square
+#[no_mangle]
+pub unsafe extern "C" fn pj_square_from_name(name: *const c_char) -> c_int {
+    match str_arg(name).and_then(|name| name.parse::<Square>().ok()) {
+        Some(sq) => sq.0 as c_int,
+        None => PJ_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn positions() {
+        unsafe {
+            let pos = pj_position_new();
+            let mut buf = [0 as c_char; 128];
+
+            let len = pj_position_get_fen(pos, buf.as_mut_ptr(), buf.len());
+            let fen = CStr::from_ptr(buf.as_ptr()).to_str().unwrap();
+            assert_eq!(len, fen.len());
+            assert_eq!(fen, Position::from_fen("startpos").get_fen());
+
+            // Short buffers are truncated but still terminated
+            assert_eq!(pj_position_get_fen(pos, buf.as_mut_ptr(), 4), len);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str().unwrap(), "s-p");
+
+            for bad in ["not a fen", "6/7/6/7/6/7/6/6 w 0 1"] {
+                let bad = CString::new(bad).unwrap();
+                assert_eq!(pj_position_set_fen(pos, bad.as_ptr()), PJ_ERROR);
+            }
+            assert_eq!(pj_position_num_legal_moves(pos), 186);
+
+            let mv = CString::new("a1a1").unwrap();
+            assert_eq!(pj_position_make_move(pos, mv.as_ptr()), PJ_ERROR);
+            let mv = CString::new("b1c1").unwrap();
+            assert_eq!(pj_position_make_move(pos, mv.as_ptr()), PJ_OK);
+            assert_eq!(pj_position_turn(pos), PJ_BLACK);
+
+            let mut stack = PjStack {
+                side: 0,
+                bottom: 0,
+                top: 0,
+            };
+            let name = CString::new("b4").unwrap();
+            let sq = pj_square_from_name(name.as_ptr());
+            assert_eq!(pj_position_stack(pos, sq, &mut stack), 1);
+            assert_eq!(
+                stack,
+                PjStack {
+                    side: PJ_WHITE,
+                    bottom: PJ_WISE,
+                    top: PJ_WISE,
+                }
+            );
+            assert_eq!(pj_position_stack(pos, 22, &mut stack), 0);
+            assert_eq!(pj_position_stack(pos, PJ_NUM_SQUARES, &mut stack), PJ_ERROR);
+
+            pj_position_free(pos);
+            assert_eq!(pj_position_result(ptr::null()), PJ_ERROR);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::{env, path::PathBuf, process::Command};
+
+// Test binaries live in target/<profile>/deps, the static library is built
+// alongside them and only copied up a level by a plain cargo build
+fn static_library() -> PathBuf {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let name = "libpijersi_ffi.a";
+    [deps.join(name), deps.parent().unwrap().join(name)]
+        .into_iter()
+        .find(|path| path.exists())
+        .expect("static library not built")
+}
+
+#[test]
+fn c_program() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let library = static_library();
+    let program = library.with_file_name(format!("pijersi_ffi_perft{}", env::consts::EXE_SUFFIX));
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg(manifest.join("tests").join("perft.c"))
+        .arg(&library)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success());
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
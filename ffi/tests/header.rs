@@ -0,0 +1,75 @@
+use std::{env, fs, path::PathBuf};
+
+use cbindgen::{Builder, Config, DocumentationStyle, Language, Style};
+use pijersi_ffi::PJ_NUM_SQUARES;
+
+const PREAMBLE: &str = "\
+/*
+ * C interface to the pijersi rules engine.
+ *
+ * Generated from ffi/src/lib.rs by cbindgen, don't edit it by hand. Rerun
+ * UPDATE_HEADER=1 cargo test -p pijersi-ffi --test header instead.
+ *
+ * Positions are created with pj_position_new and released with
+ * pj_position_free. Every PjPosition pointer passed in must come from
+ * pj_position_new and not have been freed, or be NULL, in which case the
+ * function returns its error value. String arguments must be NUL terminated.
+ *
+ * Functions that produce strings take a buffer and its size. They write as
+ * much as fits followed by a NUL and return the full length without the NUL,
+ * so a return value >= len means the output was truncated. Passing a NULL
+ * buffer or a size of 0 only returns the length.
+ */";
+
+fn generate() -> String {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let mut config = Config {
+        language: Language::C,
+        style: Style::Type,
+        header: Some(PREAMBLE.to_string()),
+        include_guard: Some("PIJERSI_H".to_string()),
+        cpp_compat: true,
+        no_includes: true,
+        sys_includes: vec!["stddef.h".to_string(), "stdint.h".to_string()],
+        usize_is_size_t: true,
+        documentation_style: DocumentationStyle::Doxy,
+        // cbindgen can't follow NUM_SQUARES into pijersi, so write the value
+        // this build has
+        after_includes: Some(format!(
+            "\n/* Squares on the board pijersi was built for, 45 on the standard one */\n\
+             #define PJ_NUM_SQUARES {}",
+            PJ_NUM_SQUARES
+        )),
+        ..Config::default()
+    };
+    config.export.exclude.push("PJ_NUM_SQUARES".to_string());
+
+    let mut header = vec![];
+    Builder::new()
+        .with_config(config)
+        .with_src(manifest.join("src").join("lib.rs"))
+        .generate()
+        .expect("cbindgen failed")
+        .write(&mut header);
+    String::from_utf8(header).unwrap()
+}
+
+// The checked in header has to be what cbindgen makes of lib.rs now
+#[test]
+fn header() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("include")
+        .join("pijersi.h");
+    let generated = generate();
+
+    if env::var_os("UPDATE_HEADER").is_some() {
+        fs::write(&path, generated).unwrap();
+    } else {
+        let current = fs::read_to_string(&path).unwrap();
+        assert!(
+            current == generated,
+            "{} is out of date, rerun with UPDATE_HEADER=1",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,70 @@
+/* Links against the static library and drives it through the C header */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "pijersi.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+                    __LINE__, #cond);                                      \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+int main(void) {
+    static const uint64_t perfts[] = {1, 186, 34054};
+    char buf[8192];
+    PjStack stack;
+    int depth;
+
+    PjPosition *pos = pj_position_new();
+    CHECK(pos != NULL);
+
+    for (depth = 0; depth < 3; depth++) {
+        CHECK(pj_position_perft(pos, depth) == perfts[depth]);
+    }
+
+    CHECK(pj_position_turn(pos) == PJ_WHITE);
+    CHECK(pj_position_result(pos) == PJ_ONGOING);
+    CHECK(pj_position_num_legal_moves(pos) == 186);
+
+    /* The size comes back first so the buffer can be checked */
+    size_t len = pj_position_legal_moves(pos, NULL, 0);
+    CHECK(len < sizeof(buf));
+    CHECK(pj_position_legal_moves(pos, buf, sizeof(buf)) == len);
+    CHECK(strlen(buf) == len);
+    CHECK(strstr(buf, "b1c1") != NULL);
+
+    CHECK(pj_position_make_move(pos, "a1a1") == PJ_ERROR);
+    CHECK(pj_position_make_move(pos, "b1c1") == PJ_OK);
+    CHECK(pj_position_turn(pos) == PJ_BLACK);
+
+    CHECK(pj_position_stack(pos, pj_square_from_name("c1"), &stack) == 1);
+    CHECK(stack.side == PJ_WHITE);
+    CHECK(stack.bottom == PJ_PAPER);
+    CHECK(stack.top == PJ_NONE);
+    CHECK(pj_position_stack(pos, pj_square_from_name("b1"), &stack) == 0);
+    CHECK(pj_position_stack(pos, PJ_NUM_SQUARES, &stack) == PJ_ERROR);
+    CHECK(pj_square_from_name("b8") == PJ_ERROR);
+
+    /* FENs round trip, bad ones leave the position alone */
+    const char *fen = "6/7/6/3R-r-2/6/7/6 w 0 1";
+    CHECK(pj_position_set_fen(pos, fen) == PJ_OK);
+    CHECK(pj_position_get_fen(pos, buf, sizeof(buf)) == strlen(fen));
+    CHECK(strcmp(buf, fen) == 0);
+    CHECK(pj_position_set_fen(pos, "not a fen") == PJ_ERROR);
+    CHECK(pj_position_get_fen(pos, buf, 4) == strlen(fen));
+    CHECK(strcmp(buf, "6/7") == 0);
+
+    pj_position_free(pos);
+
+    if (failures == 0) {
+        printf("ok\n");
+    }
+    return failures != 0;
+}
@@ -0,0 +1,46 @@
+#![no_std]
+
+// Uses pijersi without its std and alloc features. Build or test this crate on
+// its own (cargo test -p nostd) so the rest of the workspace doesn't switch
+// those features back on, cargo build -p pijersi --no-default-features checks
+// the library itself.
+
+use pijersi::{
+    position::Position,
+    random::{playout, SplitMix64},
+    result::GameResult,
+};
+
+#[must_use]
+pub fn perft(fen: &str, depth: i32) -> u64 {
+    Position::from_fen(fen).perft(depth)
+}
+
+#[must_use]
+pub fn random_game(seed: u64) -> (Option<GameResult>, i32) {
+    let pos = Position::from_fen("startpos");
+    playout(&pos, &mut SplitMix64::new(seed), 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfts() {
+        let tests = [(1, 186), (2, 34_054), (3, 6_410_472)];
+
+        for (depth, nodes) in tests {
+            assert_eq!(perft("startpos", depth), nodes);
+        }
+    }
+
+    #[test]
+    fn games() {
+        for seed in 0..100 {
+            let (result, plies) = random_game(seed);
+            assert!(result.is_some());
+            assert!(plies <= 20);
+        }
+    }
+}
@@ -1,23 +1,30 @@
-use std::{
+use core::{
     fmt,
     ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not},
 };
 
-use crate::square::Square;
+use crate::{geometry::SHAPE, side::Side, square::Square};
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Bitboard(pub u64);
 
+#[repr(u64)]
 pub enum Bitmasks {
-    All = 0x1fffffffffff,
-    ShortRows = 0x1f80fc07e03f,
-    LongRows = 0x7f03f81fc0,
-    Leftmost = 0x8104082041,
-    Rightmost = 0x104082041020,
-    WhiteHome = 0x3f,
-    BlackHome = 0x1f8000000000,
+    All = SHAPE.all(),
+    ShortRows = SHAPE.short_rows(),
+    LongRows = SHAPE.long_rows(),
+    Leftmost = SHAPE.leftmost(),
+    Rightmost = SHAPE.rightmost(),
+    WhiteHome = SHAPE.home_row(Side::White),
+    BlackHome = SHAPE.home_row(Side::Black),
 }
 
+// A diagonal step is a shift by a row length, these are the squares a step
+// off the end of a row would wrap onto
+const SHORT: u32 = SHAPE.short as u32;
+const LONG_STARTS: u64 = SHAPE.leftmost() & SHAPE.long_rows();
+const LONG_ENDS: u64 = SHAPE.rightmost() & SHAPE.long_rows();
+
 impl Bitboard {
     #[must_use]
     pub fn empty() -> Self {
@@ -96,29 +103,28 @@ impl Bitboard {
 
     #[must_use]
     pub fn up_left(&self) -> Self {
-        Bitboard(self.0 << 6) & !Bitboard(0x4002001000u64)
+        Bitboard(self.0 << SHORT) & !Bitboard(LONG_ENDS)
     }
 
     #[must_use]
     pub fn up_right(&self) -> Self {
-        Bitboard(self.0 << 7) & !Bitboard(0x100080040u64)
+        Bitboard(self.0 << (SHORT + 1)) & !Bitboard(LONG_STARTS)
     }
 
     #[must_use]
     pub fn down_left(&self) -> Self {
-        Bitboard(self.0 >> 7) & !Bitboard(0x4002001000u64)
+        Bitboard(self.0 >> (SHORT + 1)) & !Bitboard(LONG_ENDS)
     }
 
     #[must_use]
     pub fn down_right(&self) -> Self {
-        Bitboard(self.0 >> 6) & !Bitboard(0x100080040u64)
+        Bitboard(self.0 >> SHORT) & !Bitboard(LONG_STARTS)
     }
 }
 
 impl Not for Bitboard {
     type Output = Self;
 
-    #[must_use]
     fn not(self) -> Self {
         Bitboard(!self.0 & Bitmasks::All as u64)
     }
@@ -127,7 +133,6 @@ impl Not for Bitboard {
 impl BitAnd for Bitboard {
     type Output = Self;
 
-    #[must_use]
     fn bitand(self, rhs: Self) -> Self::Output {
         Bitboard(self.0 & rhs.0)
     }
@@ -136,7 +141,6 @@ impl BitAnd for Bitboard {
 impl BitOr for Bitboard {
     type Output = Self;
 
-    #[must_use]
     fn bitor(self, rhs: Self) -> Self {
         Bitboard(self.0 | rhs.0)
     }
@@ -145,40 +149,39 @@ impl BitOr for Bitboard {
 impl BitXor for Bitboard {
     type Output = Self;
 
-    #[must_use]
     fn bitxor(self, rhs: Self) -> Self::Output {
         Bitboard(self.0 ^ rhs.0)
     }
 }
 
 impl BitOrAssign for Bitboard {
-    fn bitor_assign(&mut self, rhs: Self) -> () {
+    fn bitor_assign(&mut self, rhs: Self) {
         *self = *self | rhs;
     }
 }
 
 impl BitXorAssign for Bitboard {
-    fn bitxor_assign(&mut self, rhs: Self) -> () {
+    fn bitxor_assign(&mut self, rhs: Self) {
         *self = *self ^ rhs;
     }
 }
 
 impl BitAndAssign for Bitboard {
-    fn bitand_assign(&mut self, rhs: Self) -> () {
+    fn bitand_assign(&mut self, rhs: Self) {
         *self = *self & rhs;
     }
 }
 
 impl fmt::Display for Bitboard {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for y in (0..7).rev() {
+        for y in (0..SHAPE.rows).rev() {
             let is_long = y % 2 == 1;
 
             if !is_long {
                 write!(f, " ")?;
             }
 
-            for x in 0..6 + is_long as i32 {
+            for x in 0..SHAPE.row_len(y) {
                 let sq = Square::from_coords(x, y);
 
                 if self.is_set(&sq) {
@@ -213,6 +216,7 @@ mod tests {
         assert_eq!(Bitboard(0x1) ^ Bitboard(0x2), Bitboard(0x3));
     }
 
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
     #[test]
     fn left() {
         let tests = [
@@ -234,6 +238,7 @@ mod tests {
         }
     }
 
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
     #[test]
     fn up_left() {
         let tests = [
@@ -255,6 +260,7 @@ mod tests {
         }
     }
 
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
     #[test]
     fn down_left() {
         let tests = [
@@ -276,6 +282,7 @@ mod tests {
         }
     }
 
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
     #[test]
     fn up_right() {
         let tests = [
@@ -297,6 +304,7 @@ mod tests {
         }
     }
 
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
     #[test]
     fn down_right() {
         let tests = [
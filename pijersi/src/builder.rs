@@ -0,0 +1,138 @@
+use crate::{
+    bitboard::Bitboard,
+    position::{Piece, Position},
+    side::Side,
+    square::Square,
+    stack::Stack,
+    validate::PositionError,
+};
+
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct PositionBuilder {
+    pos: Position,
+    error: Option<PositionError>,
+}
+
+impl PositionBuilder {
+    pub fn new() -> Self {
+        Self {
+            pos: Position::empty(),
+            error: None,
+        }
+    }
+
+    pub fn place(mut self, sq: Square, side: Side, bottom: Piece, top: Option<Piece>) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+
+        if !Bitboard::all().is_set(&sq) {
+            self.error = Some(PositionError::OffBoard(sq));
+        } else if self.pos.get_occupied().is_set(&sq) {
+            self.error = Some(PositionError::SquareOccupied(sq));
+        } else if top == Some(Piece::Wise) && bottom != Piece::Wise {
+            self.error = Some(PositionError::WiseOnRps(sq));
+        } else {
+            self.pos.set_stack(sq, Stack { side, bottom, top });
+        }
+
+        self
+    }
+
+    pub fn turn(mut self, side: Side) -> Self {
+        self.pos.turn = side;
+        self
+    }
+
+    pub fn halfmoves(mut self, n: i32) -> Self {
+        self.pos.halfmoves = n;
+        self
+    }
+
+    pub fn fullmoves(mut self, n: i32) -> Self {
+        self.pos.fullmoves = n;
+        self
+    }
+
+    pub fn build(self) -> Result<Position, PositionError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        self.pos.validate()?;
+
+        Ok(self.pos)
+    }
+}
+
+impl Default for PositionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, not(any(feature = "small-board", feature = "large-board"))))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn build() {
+        let pos = PositionBuilder::new()
+            .place(Square(22), Side::White, Piece::Rock, None)
+            .place(Square(23), Side::Black, Piece::Rock, None)
+            .build()
+            .unwrap();
+        assert_eq!(pos.get_fen(), "6/7/6/3R-r-2/6/7/6 w 0 1");
+
+        let pos = PositionBuilder::new()
+            .place(Square(0), Side::White, Piece::Wise, Some(Piece::Wise))
+            .place(Square(44), Side::Black, Piece::Paper, Some(Piece::Scissors))
+            .turn(Side::Black)
+            .halfmoves(7)
+            .fullmoves(4)
+            .build()
+            .unwrap();
+        assert_eq!(pos.get_fen(), "5ps/7/6/7/6/7/WW5 b 7 4");
+    }
+
+    #[test]
+    fn errors() {
+        let tests = [
+            (
+                PositionBuilder::new()
+                    .place(Square(22), Side::White, Piece::Rock, None)
+                    .place(Square(22), Side::Black, Piece::Rock, None),
+                PositionError::SquareOccupied(Square(22)),
+            ),
+            (
+                PositionBuilder::new().place(Square(45), Side::White, Piece::Rock, None),
+                PositionError::OffBoard(Square(45)),
+            ),
+            (
+                PositionBuilder::new().place(
+                    Square(0),
+                    Side::White,
+                    Piece::Rock,
+                    Some(Piece::Wise),
+                ),
+                PositionError::WiseOnRps(Square(0)),
+            ),
+            (
+                PositionBuilder::new()
+                    .place(Square(0), Side::White, Piece::Wise, Some(Piece::Wise))
+                    .place(Square(1), Side::White, Piece::Wise, None),
+                PositionError::TooManyPieces(Side::White, Piece::Wise),
+            ),
+            (
+                PositionBuilder::new().halfmoves(-3),
+                PositionError::BadHalfmoves(-3),
+            ),
+        ];
+
+        for (builder, error) in tests {
+            assert_eq!(builder.build().unwrap_err(), error);
+        }
+    }
+}
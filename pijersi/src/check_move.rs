@@ -0,0 +1,357 @@
+use core::fmt;
+
+use crate::{
+    bitboard::Bitboard,
+    mv::Mv,
+    position::{Piece, Position},
+    square::Square,
+    tables::{adjacent, JUMPS},
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IllegalMoveReason {
+    GameOver,
+    BadNotation,
+    EmptySquare(Square),
+    OpponentPiece(Square),
+    NotAStack(Square),
+    NotASolo(Square),
+    NotAdjacent(Square, Square),
+    OutOfReach(Square, Square),
+    JumpBlocked(Square),
+    CannotStackOnStack(Square),
+    WiseStacksOnWise(Square),
+    CannotCapture(Piece, Square),
+    WiseCannotCapture(Square),
+    Unknown,
+}
+
+impl fmt::Display for IllegalMoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IllegalMoveReason::GameOver => write!(f, "the game is already over"),
+            IllegalMoveReason::BadNotation => write!(f, "not a move"),
+            IllegalMoveReason::EmptySquare(sq) => write!(f, "square {} is empty", sq),
+            IllegalMoveReason::OpponentPiece(sq) => {
+                write!(f, "square {} belongs to the opponent", sq)
+            }
+            IllegalMoveReason::NotAStack(sq) => write!(f, "square {} is a solo piece", sq),
+            IllegalMoveReason::NotASolo(sq) => write!(f, "square {} is a stack", sq),
+            IllegalMoveReason::NotAdjacent(fr, to) => {
+                write!(f, "square {} isn't adjacent to {}", to, fr)
+            }
+            IllegalMoveReason::OutOfReach(fr, to) => {
+                write!(f, "square {} is out of reach from {}", to, fr)
+            }
+            IllegalMoveReason::JumpBlocked(sq) => write!(f, "the jump is blocked on {}", sq),
+            IllegalMoveReason::CannotStackOnStack(sq) => {
+                write!(f, "can't stack onto {}, stacks are at most two high", sq)
+            }
+            IllegalMoveReason::WiseStacksOnWise(sq) => {
+                write!(f, "wise can only stack onto wise, not onto {}", sq)
+            }
+            IllegalMoveReason::CannotCapture(piece, sq) => {
+                write!(f, "{:?} can't capture the piece on {}", piece, sq)
+            }
+            IllegalMoveReason::WiseCannotCapture(sq) => {
+                write!(f, "wise can't capture the piece on {}", sq)
+            }
+            IllegalMoveReason::Unknown => write!(f, "illegal move"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IllegalMoveReason {}
+
+// The squares in a move's string, the last one repeated for two square moves
+fn path(mv: &Mv) -> ([Square; 3], usize) {
+    match *mv {
+        Mv::SoloMove(fr, to) | Mv::SoloStack(fr, to) => ([fr, to, to], 2),
+        Mv::StackMove(fr, to) => ([fr, to, to], 3),
+        Mv::StackDestack(fr, to) | Mv::StackStack(fr, to) => ([fr, fr, to], 3),
+        Mv::SoloStackMove(fr, sq1, sq2)
+        | Mv::StackMoveDestack(fr, sq1, sq2)
+        | Mv::StackStackMove(fr, sq1, sq2) => ([fr, sq1, sq2], 3),
+    }
+}
+
+fn parse_path(movestr: &str) -> Option<([Square; 3], usize)> {
+    let len = movestr.len() / 2;
+    if movestr.len() != 2 * len || !(2..=3).contains(&len) {
+        return None;
+    }
+
+    let mut squares = [Square(0); 3];
+    for (i, sq) in squares.iter_mut().enumerate().take(len) {
+        *sq = movestr.get(2 * i..2 * i + 2)?.parse().ok()?;
+    }
+    squares[2] = squares[len - 1];
+
+    Some((squares, len))
+}
+
+fn beats(attacker: Piece, target: Piece) -> bool {
+    matches!(
+        (attacker, target),
+        (Piece::Rock, Piece::Scissors)
+            | (Piece::Paper, Piece::Rock)
+            | (Piece::Scissors, Piece::Paper)
+    )
+}
+
+fn step(fr: Square, to: Square) -> Option<IllegalMoveReason> {
+    if (adjacent(fr) & Bitboard::from_square(&to)).is_occupied() {
+        None
+    } else {
+        Some(IllegalMoveReason::NotAdjacent(fr, to))
+    }
+}
+
+// Stacks move one square or jump two in a straight line
+fn reach(fr: Square, to: Square, blockers: Bitboard) -> Option<IllegalMoveReason> {
+    let to_bb = Bitboard::from_square(&to);
+    if (adjacent(fr) & to_bb).is_occupied() {
+        return None;
+    }
+
+    match JUMPS[fr.0 as usize]
+        .iter()
+        .find(|jump| (jump.target & to_bb).is_occupied())
+    {
+        Some(jump) if (jump.middle & blockers).is_occupied() => {
+            Some(IllegalMoveReason::JumpBlocked(jump.middle.lsb()))
+        }
+        Some(_) => None,
+        None => Some(IllegalMoveReason::OutOfReach(fr, to)),
+    }
+}
+
+impl Position {
+    // Ok if the move is legal, otherwise the first rule it breaks
+    pub fn check_move(&self, mv: &Mv) -> Result<(), IllegalMoveReason> {
+        if self.result().is_some() {
+            return Err(IllegalMoveReason::GameOver);
+        }
+
+        if self.moves().any(|legal| legal == *mv) {
+            Ok(())
+        } else {
+            let (squares, len) = path(mv);
+            Err(self.diagnose(&squares[..len]))
+        }
+    }
+
+    // As check_move, for moves in their string form such as "a1a2a3"
+    pub fn check_move_str(&self, movestr: &str) -> Result<(), IllegalMoveReason> {
+        let (squares, len) = parse_path(movestr).ok_or(IllegalMoveReason::BadNotation)?;
+
+        if self.result().is_some() {
+            return Err(IllegalMoveReason::GameOver);
+        }
+
+        if self.moves().any(|legal| path(&legal) == (squares, len)) {
+            Ok(())
+        } else {
+            Err(self.diagnose(&squares[..len]))
+        }
+    }
+
+    // Where a piece, or a stack when it can't stack, may finish a move
+    fn land(&self, piece: Piece, to: Square, can_stack: bool) -> Option<IllegalMoveReason> {
+        let target = self.stack_on(to)?;
+
+        if target.side == self.turn {
+            if !can_stack || target.is_tall() {
+                Some(IllegalMoveReason::CannotStackOnStack(to))
+            } else if piece == Piece::Wise && target.bottom != Piece::Wise {
+                Some(IllegalMoveReason::WiseStacksOnWise(to))
+            } else {
+                None
+            }
+        } else if piece == Piece::Wise {
+            Some(IllegalMoveReason::WiseCannotCapture(to))
+        } else if !beats(piece, target.visible()) {
+            Some(IllegalMoveReason::CannotCapture(piece, to))
+        } else {
+            None
+        }
+    }
+
+    fn diagnose(&self, squares: &[Square]) -> IllegalMoveReason {
+        let fr = squares[0];
+        let Some(stack) = self.stack_on(fr) else {
+            return IllegalMoveReason::EmptySquare(fr);
+        };
+        if stack.side != self.turn {
+            return IllegalMoveReason::OpponentPiece(fr);
+        }
+
+        let piece = stack.visible();
+        let occupied = self.get_occupied();
+        let is_ours = |sq: Square| self.stack_on(sq).is_some_and(|s| s.side == self.turn);
+
+        let reason = match (stack.is_tall(), squares) {
+            // SoloMove, SoloStack
+            (false, &[_, to]) => step(fr, to).or_else(|| self.land(piece, to, true)),
+            (false, &[_, mid, _]) if mid == fr || !is_ours(mid) => {
+                Some(IllegalMoveReason::NotAStack(fr))
+            }
+            // SoloStackMove, the piece leaves its square before the stack moves
+            (false, &[_, mid, to]) => step(fr, mid)
+                .or_else(|| self.land(piece, mid, true))
+                .or_else(|| reach(mid, to, occupied ^ Bitboard::from_square(&fr)))
+                .or_else(|| {
+                    if to == fr {
+                        None
+                    } else {
+                        self.land(piece, to, false)
+                    }
+                }),
+            (true, &[_, _]) => Some(IllegalMoveReason::NotASolo(fr)),
+            // StackDestack, StackStack
+            (true, &[_, mid, to]) if mid == fr => {
+                step(fr, to).or_else(|| self.land(piece, to, true))
+            }
+            // StackMove
+            (true, &[_, mid, to]) if mid == to => {
+                reach(fr, to, occupied).or_else(|| self.land(piece, to, false))
+            }
+            // StackStackMove
+            (true, &[_, mid, to]) if is_ours(mid) => step(fr, mid)
+                .or_else(|| self.land(piece, mid, true))
+                .or_else(|| reach(mid, to, occupied))
+                .or_else(|| self.land(piece, to, false)),
+            // StackMoveDestack, the stack's old square is free to destack onto
+            (true, &[_, mid, to]) => reach(fr, mid, occupied)
+                .or_else(|| self.land(piece, mid, false))
+                .or_else(|| step(mid, to))
+                .or_else(|| {
+                    if to == fr {
+                        None
+                    } else {
+                        self.land(piece, to, true)
+                    }
+                }),
+            _ => None,
+        };
+
+        reason.unwrap_or(IllegalMoveReason::Unknown)
+    }
+}
+
+#[cfg(all(test, not(any(feature = "small-board", feature = "large-board"))))]
+mod tests {
+    use super::*;
+    use crate::geometry::NUM_SQUARES;
+
+    #[test]
+    fn illegal_moves() {
+        use IllegalMoveReason::*;
+        let sq = |s: &str| s.parse::<Square>().unwrap();
+
+        let tests = [
+            ("startpos", "", BadNotation),
+            ("startpos", "a1", BadNotation),
+            ("startpos", "a1a2a3a4", BadNotation),
+            ("startpos", "a1z9", BadNotation),
+            ("startpos", "c1c2", EmptySquare(sq("c1"))),
+            ("startpos", "f1e1", OpponentPiece(sq("f1"))),
+            ("startpos", "a1a3", NotAdjacent(sq("a1"), sq("a3"))),
+            ("startpos", "a1a1", NotAdjacent(sq("a1"), sq("a1"))),
+            ("startpos", "b4c4", NotASolo(sq("b4"))),
+            ("startpos", "a1a1a2", NotAStack(sq("a1"))),
+            ("startpos", "b1c1c2", NotAStack(sq("b1"))),
+            ("startpos", "b3b4", CannotStackOnStack(sq("b4"))),
+            ("startpos", "b4b4b3", WiseStacksOnWise(sq("b3"))),
+            (
+                "6/7/6/3R-p-2/6/7/6 w 0 1",
+                "d4d5",
+                CannotCapture(Piece::Rock, sq("d5")),
+            ),
+            (
+                "6/7/6/3R-w-2/6/7/6 w 0 1",
+                "d4d5",
+                CannotCapture(Piece::Rock, sq("d5")),
+            ),
+            (
+                "6/7/6/3W-p-2/6/7/6 w 0 1",
+                "d4d5",
+                WiseCannotCapture(sq("d5")),
+            ),
+            ("6/7/6/1RRp-4/6/7/6 w 0 1", "d2d4d4", JumpBlocked(sq("d3"))),
+            (
+                "6/7/6/1RRp-4/6/7/6 w 0 1",
+                "d2d6d6",
+                OutOfReach(sq("d2"), sq("d6")),
+            ),
+            (
+                "6/7/6/1RRR-P-3/s-5/7/6 w 0 1",
+                "d2d3d4",
+                CannotStackOnStack(sq("d4")),
+            ),
+            (
+                "6/7/6/1RRs-p-3/6/7/6 w 0 1",
+                "d2d3d4",
+                CannotCapture(Piece::Rock, sq("d4")),
+            ),
+            ("6/7/6/3R-3/6/7/6 w 0 1", "d4d5", GameOver),
+        ];
+
+        for (fen, movestr, reason) in tests {
+            let pos = Position::from_fen(fen);
+            assert_eq!(
+                pos.check_move_str(movestr),
+                Err(reason),
+                "{} {}",
+                fen,
+                movestr
+            );
+        }
+    }
+
+    #[test]
+    fn legal_moves() {
+        let fens = [
+            "startpos",
+            "6/7/6/1RRp-4/6/7/6 w 0 1",
+            "6/1wwr-4/2R-s-2/1RRp-P-1WW1/2S-3/7/6 w 0 1",
+        ];
+
+        for fen in fens {
+            let pos = Position::from_fen(fen);
+            for mv in pos.moves() {
+                assert_eq!(pos.check_move(&mv), Ok(()));
+                assert_eq!(pos.check_move_str(&mv.to_string()), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn explained() {
+        // Every illegal path from an occupied square gets a reason
+        let pos = Position::from_fen("6/1wwr-4/2R-s-2/1RRp-P-1WW1/2S-3/7/6 w 0 1");
+        let legal: Vec<_> = pos.moves().map(|mv| path(&mv)).collect();
+        let squares = || (0..NUM_SQUARES as u8).map(Square);
+
+        for fr in squares().filter(|&sq| pos.stack_on(sq).is_some()) {
+            for mid in squares() {
+                for to in squares() {
+                    let squares = [fr, mid, to];
+                    // Two square paths are stored with the last square repeated
+                    let lens = if mid == to { 2..=3 } else { 3..=3 };
+                    for len in lens {
+                        let is_legal = legal.contains(&(squares, len));
+                        let reason = pos.diagnose(&squares[..len]);
+                        assert_eq!(
+                            reason == IllegalMoveReason::Unknown,
+                            is_legal,
+                            "{:?}",
+                            &squares[..len]
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
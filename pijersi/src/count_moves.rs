@@ -1,8 +1,53 @@
 use crate::{
     bitboard::Bitboard,
     position::{Piece, Position},
+    tables::{adjacent, jumps},
 };
 
+type Shift = fn(&Bitboard) -> Bitboard;
+
+// Each direction with its opposite
+const DIRECTIONS: [(Shift, Shift); 6] = [
+    (Bitboard::left, Bitboard::right),
+    (Bitboard::right, Bitboard::left),
+    (Bitboard::up_left, Bitboard::down_right),
+    (Bitboard::down_right, Bitboard::up_left),
+    (Bitboard::up_right, Bitboard::down_left),
+    (Bitboard::down_left, Bitboard::up_right),
+];
+
+// Per square counts of up to 7, stored as three bit planes
+#[derive(Clone, Copy, Default)]
+struct Counts([Bitboard; 3]);
+
+impl Counts {
+    fn add(&mut self, bb: Bitboard) {
+        let mut carry = bb;
+        for plane in &mut self.0 {
+            let next = *plane & carry;
+            *plane ^= carry;
+            carry = next;
+        }
+    }
+
+    // Sum of the counts over the squares in bb
+    fn total(&self, bb: Bitboard) -> i32 {
+        (bb & self.0[0]).count() + 2 * (bb & self.0[1]).count() + 4 * (bb & self.0[2]).count()
+    }
+}
+
+// How many targets each square can reach in one step
+fn step_counts(targets: Bitboard) -> Counts {
+    let mut counts = Counts::default();
+    for (shift, _) in DIRECTIONS {
+        counts.add(shift(&targets));
+    }
+    counts
+}
+
+// Counts what move_generator's get_moves generates without enumerating any
+// moves. Each direction's shift is one to one, so summing popcounts over the
+// six directions counts every (from, to) pair once.
 #[must_use]
 fn get_moves(
     us: Bitboard,
@@ -13,81 +58,65 @@ fn get_moves(
     stackable: Bitboard,
     capturable: Bitboard,
 ) -> i32 {
-    let empty = !(us | them);
-    let mut nodes = 0;
-
-    // SoloMove
-    for solo in piece_mask & short {
-        nodes += (Bitboard::from_square(&solo).adjacent() & (empty | capturable)).count();
-    }
+    let occupied = us | them;
+    let empty = !occupied;
+    let targets = empty | capturable;
+    let stack_onto = us & short & stackable;
+    let solos = piece_mask & short;
+    let stacks = piece_mask & tall;
 
-    // SoloStack
-    for solo in piece_mask & short {
-        nodes += (Bitboard::from_square(&solo).adjacent() & us & short & stackable).count();
-    }
+    let mut nodes = 0;
 
-    // SoloStackMove
-    for solo in piece_mask & short {
-        for stack in Bitboard::from_square(&solo).adjacent() & us & short & stackable {
-            let bb = Bitboard::from_square(&stack);
+    // Pieces that stack and then move on as a new stack, split by direction
+    let mut stacked = [Bitboard::empty(); 6];
+    let mut restacked = [Bitboard::empty(); 6];
 
-            let blockers = (us | them) ^ Bitboard::from_square(&solo);
-            let dist1 = bb.adjacent() & (empty | capturable | Bitboard::from_square(&solo));
-            let dist2 = bb.doubles(blockers) & (empty | capturable);
+    for (i, (shift, back)) in DIRECTIONS.into_iter().enumerate() {
+        // SoloMove
+        nodes += (shift(&solos) & targets).count();
 
-            nodes += dist1.count();
-            nodes += dist2.count();
-        }
-    }
+        // SoloStack
+        stacked[i] = shift(&solos) & stack_onto;
+        nodes += stacked[i].count();
 
-    // StackMove
-    for stack in piece_mask & tall {
-        let bb = Bitboard::from_square(&stack);
-        let dist1 = bb.adjacent() & (empty | capturable);
-        let dist2 = bb.doubles(us | them) & (empty | capturable);
+        // SoloStackMove, the piece's old square is empty again so the new stack
+        // can step back onto it or jump over it
+        nodes += stacked[i].count();
+        nodes += (back(&back(&stacked[i])) & targets).count();
 
-        nodes += dist1.count();
-        nodes += dist2.count();
+        // StackStack
+        restacked[i] = shift(&stacks) & stack_onto;
+        nodes += restacked[i].count();
     }
 
-    // StackMoveDestack
-    for stack in piece_mask & tall {
-        let bb = Bitboard::from_square(&stack);
-        let dist1 = bb.adjacent() & (empty | capturable);
-        let dist2 = bb.doubles(us | them) & (empty | capturable);
-
-        for mv in dist1 {
-            nodes += (Bitboard::from_square(&mv).adjacent()
-                & (empty | stackable | capturable | bb))
-                .count();
-        }
-
-        for mv in dist2 {
-            nodes +=
-                (Bitboard::from_square(&mv).adjacent() & (empty | stackable | capturable)).count();
+    // The rest of a stack's onward moves from where it was formed, there are
+    // rarely more than a few of these so the tables are quicker
+    for bb in stacked.into_iter().chain(restacked) {
+        for sq in bb {
+            nodes += (adjacent(sq) & targets).count();
+            nodes += (jumps(sq, occupied) & targets).count();
         }
     }
 
-    // StackDestack
-    for stack in piece_mask & tall {
-        nodes += (Bitboard::from_square(&stack).adjacent() & (empty | capturable)).count();
+    if stacks.is_empty() {
+        return nodes;
     }
 
-    // StackStack
-    for stack1 in piece_mask & tall {
-        nodes += (Bitboard::from_square(&stack1).adjacent() & us & short & stackable).count();
-    }
+    // Where a destacked piece can go, the stack's old square is free after one step
+    let destacks = step_counts(empty | stackable | capturable);
 
-    // StackStackMove
-    for stack1 in piece_mask & tall {
-        for stack2 in Bitboard::from_square(&stack1).adjacent() & us & short & stackable {
-            let bb = Bitboard::from_square(&stack2);
-            let dist1 = bb.adjacent() & (empty | capturable);
-            let dist2 = bb.doubles(us | them) & (empty | capturable);
+    for (shift, _) in DIRECTIONS {
+        // StackMove
+        let dist1 = shift(&stacks) & targets;
+        let dist2 = shift(&(shift(&stacks) & empty)) & targets;
+        nodes += dist1.count() + dist2.count();
 
-            nodes += dist1.count();
-            nodes += dist2.count();
-        }
+        // StackMoveDestack
+        nodes += destacks.total(dist1) + dist1.count();
+        nodes += destacks.total(dist2);
+
+        // StackDestack
+        nodes += dist1.count();
     }
 
     nodes
@@ -96,14 +125,7 @@ fn get_moves(
 impl Position {
     #[must_use]
     pub fn count_moves(&self) -> i32 {
-        let mut count = 0;
-
-        self.move_generator(&mut |_| {
-            count += 1;
-            false
-        });
-
-        count
+        self.legal_moves().len() as i32
     }
 
     #[must_use]
@@ -157,3 +179,49 @@ impl Position {
         nodes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::random::{Rng, SplitMix64};
+
+    #[test]
+    fn counters() {
+        let mut counts = Counts::default();
+        for n in 1..=7 {
+            counts.add(Bitboard(0b1));
+            counts.add(Bitboard(0b10) & Bitboard((n % 2) << 1));
+            assert_eq!(counts.total(Bitboard(0b1)), n as i32);
+        }
+        assert_eq!(counts.total(Bitboard(0b11)), 7 + 4);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sneaky_random() {
+        let mut rng = SplitMix64::new(0);
+
+        for game in 0..500 {
+            let mut pos = if game % 2 == 0 {
+                Position::from_fen("startpos")
+            } else {
+                Position::shuffled_start(rng.next_u64())
+            };
+
+            loop {
+                assert_eq!(
+                    pos.count_moves_sneaky(),
+                    pos.count_moves(),
+                    "{}",
+                    pos.get_fen()
+                );
+                if pos.is_gameover() {
+                    break;
+                }
+                let moves = pos.legal_moves();
+                pos.makemove(&moves[rng.below(moves.len() as u32) as usize]);
+            }
+        }
+    }
+}
@@ -0,0 +1,391 @@
+// Position records for test suites and benchmarks: a FEN followed by
+// operations, all separated by semicolons, for example
+//   startpos; id "start"; perft 1 186; perft 2 34054
+// Files hold one record per line, blank lines and lines starting with # are
+// skipped. Inside a quoted string \" is a quote and \\ a backslash.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, str::FromStr};
+
+use crate::{
+    position::Position,
+    result::{result_str, GameResult},
+    set_fen::FenError,
+};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Operation {
+    Id(String),
+    BestMove(Vec<String>),
+    AvoidMove(Vec<String>),
+    Perft(i32, u64),
+    Result(Option<GameResult>),
+    Comment(u8, String),
+    // Anything else is kept as written so records survive a round trip
+    Other(String, String),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum EpdError {
+    MissingFen,
+    BadFen(FenError),
+    UnterminatedString,
+    MissingOperand(String),
+    BadOperand(String, String),
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpdError::MissingFen => write!(f, "record has no fen"),
+            EpdError::BadFen(err) => write!(f, "bad fen: {}", err),
+            EpdError::UnterminatedString => write!(f, "string is missing its closing quote"),
+            EpdError::MissingOperand(op) => write!(f, "operation {} is missing an operand", op),
+            EpdError::BadOperand(op, operand) => {
+                write!(f, "operation {} has an invalid operand '{}'", op, operand)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EpdError {}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Record {
+    pub fen: String,
+    pub ops: Vec<Operation>,
+}
+
+// Splits on semicolons that aren't inside a quoted string
+fn split_fields(line: &str) -> Result<Vec<&str>, EpdError> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                fields.push(&line[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if quoted {
+        return Err(EpdError::UnterminatedString);
+    }
+    fields.push(&line[start..]);
+    Ok(fields)
+}
+
+// Quotes are optional around a single word
+fn parse_string(operand: &str) -> String {
+    match operand.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(inner) => {
+            let mut text = String::new();
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => text.extend(chars.next()),
+                    _ => text.push(c),
+                }
+            }
+            text
+        }
+        None => operand.to_string(),
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in text.chars() {
+        if c == '"' || c == '\\' {
+            write!(f, "\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    write!(f, "\"")
+}
+
+impl FromStr for Operation {
+    type Err = EpdError;
+
+    fn from_str(field: &str) -> Result<Self, Self::Err> {
+        let (opcode, operand) = match field.split_once(char::is_whitespace) {
+            Some((opcode, operand)) => (opcode, operand.trim()),
+            None => (field, ""),
+        };
+        let bad = || EpdError::BadOperand(opcode.to_string(), operand.to_string());
+
+        if operand.is_empty() {
+            return Err(EpdError::MissingOperand(opcode.to_string()));
+        }
+
+        match opcode {
+            "id" => Ok(Operation::Id(parse_string(operand))),
+            "bm" => Ok(Operation::BestMove(
+                operand.split_whitespace().map(String::from).collect(),
+            )),
+            "am" => Ok(Operation::AvoidMove(
+                operand.split_whitespace().map(String::from).collect(),
+            )),
+            "perft" => match *operand.split_whitespace().collect::<Vec<&str>>() {
+                [depth, nodes] => Ok(Operation::Perft(
+                    depth.parse().map_err(|_| bad())?,
+                    nodes.parse().map_err(|_| bad())?,
+                )),
+                _ => Err(bad()),
+            },
+            "result" => match operand {
+                "white" => Ok(Operation::Result(Some(GameResult::WhiteWin))),
+                "black" => Ok(Operation::Result(Some(GameResult::BlackWin))),
+                "draw" => Ok(Operation::Result(Some(GameResult::Draw))),
+                "none" => Ok(Operation::Result(None)),
+                _ => Err(bad()),
+            },
+            _ => match opcode.strip_prefix('c').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n) if n < 10 && opcode.len() == 2 => {
+                    Ok(Operation::Comment(n, parse_string(operand)))
+                }
+                _ => Ok(Operation::Other(opcode.to_string(), operand.to_string())),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Id(id) => {
+                write!(f, "id ")?;
+                write_string(f, id)
+            }
+            Operation::BestMove(moves) => write!(f, "bm {}", moves.join(" ")),
+            Operation::AvoidMove(moves) => write!(f, "am {}", moves.join(" ")),
+            Operation::Perft(depth, nodes) => write!(f, "perft {} {}", depth, nodes),
+            Operation::Result(result) => write!(f, "result {}", result_str(*result)),
+            Operation::Comment(n, text) => {
+                write!(f, "c{} ", n)?;
+                write_string(f, text)
+            }
+            Operation::Other(opcode, operand) => write!(f, "{} {}", opcode, operand),
+        }
+    }
+}
+
+impl Record {
+    #[must_use]
+    pub fn new(fen: &str) -> Self {
+        Self {
+            fen: fen.to_string(),
+            ops: Vec::new(),
+        }
+    }
+
+    // Suites may hold positions validate() rejects, only the FEN has to be well formed
+    pub fn position(&self) -> Result<Position, EpdError> {
+        Position::try_from_fen(&self.fen).map_err(EpdError::BadFen)
+    }
+
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        self.ops.iter().find_map(|op| match op {
+            Operation::Id(id) => Some(id.as_str()),
+            _ => None,
+        })
+    }
+
+    // Expected node counts as (depth, nodes) in the order they're listed
+    #[must_use]
+    pub fn perft(&self) -> Vec<(i32, u64)> {
+        self.ops
+            .iter()
+            .filter_map(|op| match op {
+                Operation::Perft(depth, nodes) => Some((*depth, *nodes)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Record {
+    type Err = EpdError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields = split_fields(line)?;
+        let fen = fields[0].trim();
+        if fen.is_empty() {
+            return Err(EpdError::MissingFen);
+        }
+
+        let mut record = Record::new(fen);
+        record.position()?;
+        for field in &fields[1..] {
+            let field = field.trim();
+            if !field.is_empty() {
+                record.ops.push(field.parse()?);
+            }
+        }
+
+        Ok(record)
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.fen)?;
+        for op in &self.ops {
+            write!(f, "; {}", op)?;
+        }
+        Ok(())
+    }
+}
+
+// Every record in a file, or the first error with its line number counting from 1
+pub fn parse_records(text: &str) -> Result<Vec<Record>, (usize, EpdError)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(n, line)| line.parse().map_err(|err| (n, err)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn records() {
+        use alloc::vec;
+        let tests = [
+            ("startpos", Record::new("startpos")),
+            (
+                "startpos; id \"start\"; perft 1 186; perft 2 34054",
+                Record {
+                    fen: "startpos".to_string(),
+                    ops: vec![
+                        Operation::Id("start".to_string()),
+                        Operation::Perft(1, 186),
+                        Operation::Perft(2, 34054),
+                    ],
+                },
+            ),
+            (
+                "6/7/6/R-s-5/6/7/6 w 0 1; bm d1d2 d1d1d2; am d1c1; result none",
+                Record {
+                    fen: "6/7/6/R-s-5/6/7/6 w 0 1".to_string(),
+                    ops: vec![
+                        Operation::BestMove(vec!["d1d2".to_string(), "d1d1d2".to_string()]),
+                        Operation::AvoidMove(vec!["d1c1".to_string()]),
+                        Operation::Result(None),
+                    ],
+                },
+            ),
+            (
+                "r-R-4/7/6/7/6/7/6 w 0 1; result white; c0 \"goal; reached\"; c9 done",
+                Record {
+                    fen: "r-R-4/7/6/7/6/7/6 w 0 1".to_string(),
+                    ops: vec![
+                        Operation::Result(Some(GameResult::WhiteWin)),
+                        Operation::Comment(0, "goal; reached".to_string()),
+                        Operation::Comment(9, "done".to_string()),
+                    ],
+                },
+            ),
+            (
+                "startpos; id \"say \\\"hi\\\"\"; c1 \"a\\\\b\"",
+                Record {
+                    fen: "startpos".to_string(),
+                    ops: vec![
+                        Operation::Id("say \"hi\"".to_string()),
+                        Operation::Comment(1, "a\\b".to_string()),
+                    ],
+                },
+            ),
+            (
+                "startpos; c10 x; depth 5 ;",
+                Record {
+                    fen: "startpos".to_string(),
+                    ops: vec![
+                        Operation::Other("c10".to_string(), "x".to_string()),
+                        Operation::Other("depth".to_string(), "5".to_string()),
+                    ],
+                },
+            ),
+        ];
+
+        for (line, expected) in tests {
+            let record: Record = line.parse().unwrap();
+            assert_eq!(record, expected, "{}", line);
+
+            // Written records read back the same
+            let written = record.to_string();
+            assert_eq!(written.parse::<Record>().unwrap(), record, "{}", written);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let tests = [
+            ("", EpdError::MissingFen),
+            ("; id x", EpdError::MissingFen),
+            (
+                "6/7/6/7/6/7/6/6 w 0 1; id x",
+                EpdError::BadFen(FenError::WrongRowCount(8)),
+            ),
+            ("startpos; c0 \"open\\\"", EpdError::UnterminatedString),
+            ("startpos; c0 \"open", EpdError::UnterminatedString),
+            ("startpos; bm", EpdError::MissingOperand("bm".to_string())),
+            (
+                "startpos; perft 1",
+                EpdError::BadOperand("perft".to_string(), "1".to_string()),
+            ),
+            (
+                "startpos; perft x 1",
+                EpdError::BadOperand("perft".to_string(), "x 1".to_string()),
+            ),
+            (
+                "startpos; result maybe",
+                EpdError::BadOperand("result".to_string(), "maybe".to_string()),
+            ),
+        ];
+
+        for (line, err) in tests {
+            assert_eq!(line.parse::<Record>(), Err(err), "{}", line);
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn files() {
+        use alloc::vec;
+        let text = "# Perft\nstartpos; perft 1 186\n\n  6/7/6/R-s-5/6/7/6 w 0 1; id two\n";
+        let records = parse_records(text).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].perft(), vec![(1, 186)]);
+        assert_eq!(records[1].id(), Some("two"));
+        assert_eq!(
+            records[1].position().unwrap().get_fen(),
+            "6/7/6/R-s-5/6/7/6 w 0 1"
+        );
+
+        assert_eq!(
+            parse_records("startpos\nstartpos; perft 1\n"),
+            Err((
+                2,
+                EpdError::BadOperand("perft".to_string(), "1".to_string())
+            ))
+        );
+    }
+}
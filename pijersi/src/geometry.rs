@@ -0,0 +1,541 @@
+use crate::{bitboard::Bitboard, side::Side, square::Square};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Direction {
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 6] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::UpLeft => Direction::DownRight,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::DownRight => Direction::UpLeft,
+        }
+    }
+
+    // Axial (q, r) step, r increases towards black's home row
+    #[must_use]
+    pub const fn axial(&self) -> (i32, i32) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, 1),
+            Direction::UpRight => (0, 1),
+            Direction::DownLeft => (0, -1),
+            Direction::DownRight => (1, -1),
+        }
+    }
+}
+
+// Rows alternate between short and one square longer, starting with a short
+// row on white's side. Long rows stick out half a square to the left of the
+// short rows either side of them. Squares are numbered along each row from
+// white's side, so a shape fits in a bitboard while it has at most 64 squares.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BoardShape {
+    pub rows: i32,
+    pub short: i32,
+}
+
+impl BoardShape {
+    pub const STANDARD: BoardShape = BoardShape { rows: 7, short: 6 };
+    pub const SMALL: BoardShape = BoardShape { rows: 5, short: 6 };
+    pub const LARGE: BoardShape = BoardShape { rows: 9, short: 6 };
+
+    // Fits in a bitboard, with rows named a, b, c... and files 1 to 9
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        self.rows >= 2
+            && self.rows <= 26
+            && self.short >= 2
+            && self.short <= 8
+            && self.num_squares() <= 64
+    }
+
+    // Room for the start position: both sides' two home rows with a row
+    // between them, ends that match when the board is turned around for
+    // black, and no more of a piece than a side may have
+    #[must_use]
+    pub const fn has_start(&self) -> bool {
+        self.rows >= 5 && self.rows % 2 == 1 && self.short <= 6
+    }
+
+    #[must_use]
+    pub const fn row_len(&self, y: i32) -> i32 {
+        self.short + y % 2
+    }
+
+    #[must_use]
+    pub const fn row_start(&self, y: i32) -> i32 {
+        self.short * y + y / 2
+    }
+
+    #[must_use]
+    pub const fn num_squares(&self) -> usize {
+        self.row_start(self.rows) as usize
+    }
+
+    #[must_use]
+    pub const fn index(&self, x: i32, y: i32) -> Option<Square> {
+        if y < 0 || y >= self.rows || x < 0 || x >= self.row_len(y) {
+            None
+        } else {
+            Some(Square((self.row_start(y) + x) as u8))
+        }
+    }
+
+    // (x, y) with x along the row and y the row counting from white's side
+    #[must_use]
+    pub const fn coords(&self, sq: Square) -> (i32, i32) {
+        let mut y = 0;
+        while self.row_start(y + 1) <= sq.0 as i32 {
+            y += 1;
+        }
+        (sq.0 as i32 - self.row_start(y), y)
+    }
+
+    #[must_use]
+    pub const fn axial(&self, sq: Square) -> (i32, i32) {
+        let (x, y) = self.coords(sq);
+        let col = 2 * x - y % 2;
+        ((col - y) / 2, y)
+    }
+
+    #[must_use]
+    pub const fn from_axial(&self, q: i32, r: i32) -> Option<Square> {
+        if r < 0 {
+            return None;
+        }
+        let col = 2 * q + r;
+        self.index((col + r % 2) / 2, r)
+    }
+
+    #[must_use]
+    pub const fn row(&self, y: i32) -> u64 {
+        ((1 << self.row_len(y)) - 1) << self.row_start(y)
+    }
+
+    #[must_use]
+    pub const fn all(&self) -> u64 {
+        u64::MAX >> (64 - self.num_squares())
+    }
+
+    #[must_use]
+    pub const fn short_rows(&self) -> u64 {
+        let mut mask = 0;
+        let mut y = 0;
+        while y < self.rows {
+            mask |= self.row(y);
+            y += 2;
+        }
+        mask
+    }
+
+    #[must_use]
+    pub const fn long_rows(&self) -> u64 {
+        self.all() & !self.short_rows()
+    }
+
+    #[must_use]
+    pub const fn leftmost(&self) -> u64 {
+        let mut mask = 0;
+        let mut y = 0;
+        while y < self.rows {
+            mask |= 1 << self.row_start(y);
+            y += 1;
+        }
+        mask
+    }
+
+    #[must_use]
+    pub const fn rightmost(&self) -> u64 {
+        let mut mask = 0;
+        let mut y = 0;
+        while y < self.rows {
+            mask |= 1 << (self.row_start(y) + self.row_len(y) - 1);
+            y += 1;
+        }
+        mask
+    }
+
+    // The row each side starts from, which is the other side's goal
+    #[must_use]
+    pub const fn home_row(&self, side: Side) -> u64 {
+        match side {
+            Side::White => self.row(0),
+            Side::Black => self.row(self.rows - 1),
+        }
+    }
+
+    // Moving diagonally is a shift by a row length, the ends of the long rows
+    // are where a shifted square wraps onto the wrong row
+    #[must_use]
+    pub const fn shift(&self, bb: u64, dir: Direction) -> u64 {
+        let all = self.all();
+        let long = self.long_rows();
+        let long_starts = self.leftmost() & long;
+        let long_ends = self.rightmost() & long;
+        let short = self.short as u32;
+
+        match dir {
+            Direction::Left => (bb >> 1) & !self.rightmost(),
+            Direction::Right => (bb << 1) & !self.leftmost() & all,
+            Direction::UpLeft => (bb << short) & !long_ends & all,
+            Direction::UpRight => (bb << (short + 1)) & !long_starts & all,
+            Direction::DownLeft => (bb >> (short + 1)) & !long_ends,
+            Direction::DownRight => (bb >> short) & !long_starts,
+        }
+    }
+}
+
+// The board everything is built for, picked by feature. Masks, square names,
+// FEN rows, goal rows and the start position all follow from it. Features
+// only add, so with both on large-board wins.
+#[cfg(not(any(feature = "small-board", feature = "large-board")))]
+pub const SHAPE: BoardShape = BoardShape::STANDARD;
+#[cfg(all(feature = "small-board", not(feature = "large-board")))]
+pub const SHAPE: BoardShape = BoardShape::SMALL;
+#[cfg(feature = "large-board")]
+pub const SHAPE: BoardShape = BoardShape::LARGE;
+
+pub const NUM_SQUARES: usize = SHAPE.num_squares();
+
+const _: () = assert!(SHAPE.is_valid() && SHAPE.has_start());
+
+const AXIAL: [(i32, i32); NUM_SQUARES] = {
+    let mut table = [(0, 0); NUM_SQUARES];
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        table[sq] = SHAPE.axial(Square(sq as u8));
+        sq += 1;
+    }
+    table
+};
+
+pub(crate) const NEIGHBOURS: [[Option<Square>; 6]; NUM_SQUARES] = {
+    let mut table = [[None; 6]; NUM_SQUARES];
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        let (q, r) = AXIAL[sq];
+        let mut dir = 0;
+        while dir < 6 {
+            let (dq, dr) = Direction::ALL[dir].axial();
+            table[sq][dir] = SHAPE.from_axial(q + dq, r + dr);
+            dir += 1;
+        }
+        sq += 1;
+    }
+    table
+};
+
+// Each square's image in the line down the middle of the board
+const MIRRORS: [Square; NUM_SQUARES] = {
+    let mut table = [Square(0); NUM_SQUARES];
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        let (x, y) = SHAPE.coords(Square(sq as u8));
+        table[sq] = Square((SHAPE.row_start(y) + SHAPE.row_len(y) - 1 - x) as u8);
+        sq += 1;
+    }
+    table
+};
+
+const DISTANCES: [[u8; NUM_SQUARES]; NUM_SQUARES] = {
+    let mut table = [[0; NUM_SQUARES]; NUM_SQUARES];
+    let mut a = 0;
+    while a < NUM_SQUARES {
+        let mut b = 0;
+        while b < NUM_SQUARES {
+            let dq = AXIAL[a].0 - AXIAL[b].0;
+            let dr = AXIAL[a].1 - AXIAL[b].1;
+            table[a][b] = ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as u8;
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+};
+
+impl Square {
+    #[must_use]
+    pub fn neighbour(&self, dir: Direction) -> Option<Square> {
+        NEIGHBOURS[self.0 as usize][dir as usize]
+    }
+
+    pub fn neighbours(&self) -> impl Iterator<Item = Square> {
+        NEIGHBOURS[self.0 as usize].into_iter().flatten()
+    }
+
+    // Rows are centred, so mirroring left to right keeps every neighbour
+    #[must_use]
+    pub fn mirrored(&self) -> Square {
+        MIRRORS[self.0 as usize]
+    }
+
+    #[must_use]
+    pub fn distance(&self, other: Square) -> i32 {
+        DISTANCES[self.0 as usize][other.0 as usize] as i32
+    }
+
+    // Steps needed to reach any square on the given side's home row
+    #[must_use]
+    pub fn home_distance(&self, side: Side) -> i32 {
+        match side {
+            Side::White => self.rank(),
+            Side::Black => SHAPE.rows - 1 - self.rank(),
+        }
+    }
+
+    #[must_use]
+    pub fn to_axial(&self) -> (i32, i32) {
+        AXIAL[self.0 as usize]
+    }
+
+    #[must_use]
+    pub fn from_axial(q: i32, r: i32) -> Option<Square> {
+        SHAPE.from_axial(q, r)
+    }
+
+    #[must_use]
+    pub fn to_cube(&self) -> (i32, i32, i32) {
+        let (q, r) = self.to_axial();
+        (q, -q - r, r)
+    }
+
+    #[must_use]
+    pub fn from_cube(x: i32, y: i32, z: i32) -> Option<Square> {
+        if x + y + z != 0 {
+            None
+        } else {
+            SHAPE.from_axial(x, z)
+        }
+    }
+}
+
+impl Bitboard {
+    #[must_use]
+    pub fn shift(&self, dir: Direction) -> Self {
+        match dir {
+            Direction::Left => self.left(),
+            Direction::Right => self.right(),
+            Direction::UpLeft => self.up_left(),
+            Direction::UpRight => self.up_right(),
+            Direction::DownLeft => self.down_left(),
+            Direction::DownRight => self.down_right(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Bitmasks;
+
+    fn squares() -> impl Iterator<Item = Square> {
+        (0..NUM_SQUARES as u8).map(Square)
+    }
+
+    #[test]
+    fn neighbours() {
+        for sq in squares() {
+            for dir in Direction::ALL {
+                let expected = Bitboard::from_square(&sq).shift(dir);
+                let got = sq
+                    .neighbour(dir)
+                    .map_or(Bitboard::empty(), |n| Bitboard::from_square(&n));
+                assert_eq!(got, expected);
+
+                if let Some(n) = sq.neighbour(dir) {
+                    assert_eq!(n.neighbour(dir.opposite()), Some(sq));
+                }
+            }
+
+            let all = sq
+                .neighbours()
+                .fold(Bitboard::empty(), |bb, n| bb | Bitboard::from_square(&n));
+            assert_eq!(all, Bitboard::from_square(&sq).adjacent());
+        }
+    }
+
+    #[test]
+    fn mirrors() {
+        for sq in squares() {
+            let mirrored = sq.mirrored();
+            assert_eq!(mirrored.rank(), sq.rank());
+            assert_eq!(mirrored.mirrored(), sq);
+
+            for dir in Direction::ALL {
+                let flipped = match dir {
+                    Direction::Left => Direction::Right,
+                    Direction::Right => Direction::Left,
+                    Direction::UpLeft => Direction::UpRight,
+                    Direction::UpRight => Direction::UpLeft,
+                    Direction::DownLeft => Direction::DownRight,
+                    Direction::DownRight => Direction::DownLeft,
+                };
+                assert_eq!(
+                    sq.neighbour(dir).map(|n| n.mirrored()),
+                    mirrored.neighbour(flipped)
+                );
+            }
+        }
+
+        assert_eq!(Square(0).mirrored(), Square(5));
+        assert_eq!(Square(9).mirrored(), Square(9));
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn distances() {
+        for from in squares() {
+            let mut seen = Bitboard::from_square(&from);
+            let mut frontier = seen;
+            let mut dist = 0;
+
+            while frontier.is_occupied() {
+                for sq in frontier {
+                    assert_eq!(from.distance(sq), dist);
+                    assert_eq!(sq.distance(from), dist);
+                }
+
+                frontier = frontier.adjacent() & !seen;
+                seen |= frontier;
+                dist += 1;
+            }
+
+            assert_eq!(seen, Bitboard::all());
+        }
+
+        assert_eq!(Square(0).distance(Square(44)), 8);
+        assert_eq!(Square(0).distance(Square(5)), 5);
+        assert_eq!(Square(6).distance(Square(38)), 8);
+    }
+
+    #[test]
+    fn home_distances() {
+        for sq in squares() {
+            for (side, mask) in [
+                (Side::White, Bitboard(Bitmasks::WhiteHome as u64)),
+                (Side::Black, Bitboard(Bitmasks::BlackHome as u64)),
+            ] {
+                let min = mask.into_iter().map(|h| sq.distance(h)).min();
+                assert_eq!(Some(sq.home_distance(side)), min);
+            }
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn standard_shape() {
+        let shape = BoardShape::STANDARD;
+        assert_eq!(shape.num_squares(), 45);
+        assert_eq!(shape.all(), 0x1fffffffffff);
+        assert_eq!(shape.short_rows(), 0x1f80fc07e03f);
+        assert_eq!(shape.long_rows(), 0x7f03f81fc0);
+        assert_eq!(shape.leftmost(), 0x8104082041);
+        assert_eq!(shape.rightmost(), 0x104082041020);
+        assert_eq!(shape.home_row(Side::White), 0x3f);
+        assert_eq!(shape.home_row(Side::Black), 0x1f8000000000);
+
+        for sq in squares() {
+            let bb = Bitboard::from_square(&sq);
+            for dir in Direction::ALL {
+                assert_eq!(Bitboard(shape.shift(bb.0, dir)), bb.shift(dir));
+            }
+        }
+    }
+
+    #[test]
+    fn other_shapes() {
+        let shapes = [
+            BoardShape::STANDARD,
+            BoardShape::SMALL,
+            BoardShape { rows: 5, short: 4 },
+            BoardShape::LARGE,
+            BoardShape { rows: 2, short: 2 },
+        ];
+
+        for shape in shapes {
+            assert!(shape.is_valid(), "{:?}", shape);
+            let all = shape.all();
+            assert_eq!(all.count_ones() as usize, shape.num_squares());
+            assert_eq!(shape.short_rows() & shape.long_rows(), 0);
+            assert_eq!(shape.short_rows() | shape.long_rows(), all);
+            assert_eq!(shape.home_row(Side::White).count_ones() as i32, shape.short);
+
+            for idx in 0..shape.num_squares() as u8 {
+                let sq = Square(idx);
+                let (x, y) = shape.coords(sq);
+                assert_eq!(shape.index(x, y), Some(sq), "{:?}", shape);
+
+                let (q, r) = shape.axial(sq);
+                assert_eq!(shape.from_axial(q, r), Some(sq));
+
+                // Shifts agree with stepping in axial coordinates
+                for dir in Direction::ALL {
+                    let (dq, dr) = dir.axial();
+                    let expected = shape.from_axial(q + dq, r + dr).map_or(0, |n| 1 << n.0);
+                    assert_eq!(
+                        shape.shift(1 << idx, dir),
+                        expected,
+                        "{:?} {:?}",
+                        shape,
+                        dir
+                    );
+                }
+            }
+
+            let last = shape.rows - 1;
+            assert_eq!(shape.home_row(Side::Black), shape.row(last));
+            assert_eq!(shape.index(0, shape.rows), None);
+            assert_eq!(shape.index(shape.row_len(last), last), None);
+        }
+
+        assert!(!BoardShape { rows: 9, short: 7 }.is_valid());
+        assert!(!BoardShape { rows: 7, short: 1 }.is_valid());
+
+        assert!(BoardShape::SMALL.has_start());
+        assert!(BoardShape::LARGE.has_start());
+        assert!(!BoardShape { rows: 6, short: 6 }.has_start());
+        assert!(!BoardShape { rows: 3, short: 6 }.has_start());
+        assert!(!BoardShape { rows: 7, short: 8 }.has_start());
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn coordinates() {
+        for sq in squares() {
+            let (q, r) = sq.to_axial();
+            assert_eq!(Square::from_axial(q, r), Some(sq));
+
+            let (x, y, z) = sq.to_cube();
+            assert_eq!(x + y + z, 0);
+            assert_eq!(Square::from_cube(x, y, z), Some(sq));
+        }
+
+        assert_eq!(Square(0).to_axial(), (0, 0));
+        assert_eq!(Square(6).to_axial(), (-1, 1));
+        assert_eq!(Square(22).to_axial(), (1, 3));
+        assert_eq!(Square::from_axial(-1, 0), None);
+        assert_eq!(Square::from_axial(0, 7), None);
+        assert_eq!(Square::from_cube(0, 0, 1), None);
+    }
+}
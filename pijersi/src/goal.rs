@@ -0,0 +1,304 @@
+use crate::{
+    bitboard::{Bitboard, Bitmasks},
+    movelist::MoveList,
+    mv::Mv,
+    position::{Piece, Position},
+    side::Side,
+    square::Square,
+};
+
+// The row a side's rps pieces have to reach to win
+#[must_use]
+pub fn goal_row(side: Side) -> Bitboard {
+    match side {
+        Side::White => Bitboard(Bitmasks::BlackHome as u64),
+        Side::Black => Bitboard(Bitmasks::WhiteHome as u64),
+    }
+}
+
+impl Position {
+    // Moves needed for the piece or stack on the square to reach its goal row,
+    // moving on its own as one unit with everything else frozen. Stacks may
+    // also jump two squares over an empty square. A square can be entered if
+    // it's empty or holds an enemy the moving piece can capture.
+    #[must_use]
+    pub fn goal_distance(&self, sq: Square) -> Option<u8> {
+        let stack = self.stack_on(sq)?;
+
+        // Wise on top can't win, and neither can wise on rps in an invalid
+        // position that was loaded anyway
+        let prey = match stack.visible() {
+            Piece::Rock => Piece::Scissors,
+            Piece::Paper => Piece::Rock,
+            Piece::Scissors => Piece::Paper,
+            Piece::Wise => return None,
+        };
+
+        let goal = goal_row(stack.side);
+        let start = Bitboard::from_square(&sq);
+        if (start & goal).is_occupied() {
+            return Some(0);
+        }
+
+        let blockers = self.get_occupied() & !start;
+        let them = self.sides[!stack.side as usize];
+        let passable = !blockers | (self.get_visible(prey) & them);
+
+        let mut seen = start;
+        let mut frontier = start;
+        let mut dist = 0;
+
+        while frontier.is_occupied() {
+            let mut next = frontier.adjacent();
+            if stack.is_tall() {
+                next |= frontier.doubles(blockers);
+            }
+            frontier = next & passable & !seen & Bitboard::all();
+            seen |= frontier;
+            dist += 1;
+
+            if (frontier & goal).is_occupied() {
+                return Some(dist);
+            }
+        }
+
+        None
+    }
+
+    #[must_use]
+    pub fn min_goal_distance(&self, side: Side) -> Option<u8> {
+        (self.get_rps() & self.sides[side as usize])
+            .into_iter()
+            .filter_map(|sq| self.goal_distance(sq))
+            .min()
+    }
+
+    // Can the side put an rps piece on its goal row in one move, as if it were
+    // their turn. Winning by capturing the last enemy piece isn't counted.
+    #[must_use]
+    pub fn has_goal_threat(&self, side: Side) -> bool {
+        let us = self.sides[side as usize];
+        let them = self.sides[!side as usize];
+        let occupied = us | them;
+        let empty = !occupied;
+        let goal = goal_row(side);
+        let stackable = us & self.get_short();
+
+        let attacks = [
+            (Piece::Rock, Piece::Scissors),
+            (Piece::Paper, Piece::Rock),
+            (Piece::Scissors, Piece::Paper),
+        ];
+
+        for (piece, prey) in attacks {
+            let movers = self.get_visible(piece) & us;
+            let stacks = movers & self.get_tall();
+            let targets = empty | (self.get_visible(prey) & them);
+
+            // The piece steps there on its own, from a stack or onto one of ours
+            if (movers.adjacent() & goal & (targets | stackable)).is_occupied() {
+                return true;
+            }
+
+            // The stack gets there, or gets close enough for its top to finish
+            let reach = (stacks.adjacent() | stacks.doubles(occupied)) & targets;
+            if (reach & goal).is_occupied()
+                || (reach.adjacent() & goal & (targets | stackable)).is_occupied()
+            {
+                return true;
+            }
+
+            // The piece stacks and the new stack moves on. Jumping back over the
+            // square a solo piece left lands next to it, where it could have
+            // stepped anyway.
+            let stacked = movers.adjacent() & stackable;
+            let reach = stacked.adjacent() | stacked.doubles(occupied);
+            if (reach & goal & targets).is_occupied() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // Legal moves that put one of our rps pieces on the goal row
+    pub fn winning_moves(&self) -> impl Iterator<Item = Mv> + '_ {
+        let moves = if !self.is_gameover() && self.has_goal_threat(self.turn) {
+            self.legal_moves()
+        } else {
+            MoveList::new()
+        };
+
+        moves.into_iter().filter(|mv| self.reaches_goal(mv))
+    }
+
+    fn reaches_goal(&self, mv: &Mv) -> bool {
+        let goal = goal_row(self.turn);
+        let is_rps = |piece: Option<Piece>| piece.is_some_and(|piece| piece != Piece::Wise);
+        let visible = |sq: Square| self.stack_on(sq).map(|stack| stack.visible());
+        let bottom = |sq: Square| self.stack_on(sq).map(|stack| stack.bottom);
+
+        match *mv {
+            Mv::SoloMove(fr, to)
+            | Mv::SoloStack(fr, to)
+            | Mv::StackDestack(fr, to)
+            | Mv::StackStack(fr, to) => goal.is_set(&to) && is_rps(visible(fr)),
+            Mv::StackMove(fr, to) => {
+                goal.is_set(&to) && (is_rps(visible(fr)) || is_rps(bottom(fr)))
+            }
+            Mv::StackMoveDestack(fr, sq1, sq2) => {
+                (goal.is_set(&sq1) && is_rps(bottom(fr)))
+                    || (goal.is_set(&sq2) && is_rps(visible(fr)))
+            }
+            Mv::SoloStackMove(fr, sq1, sq2) | Mv::StackStackMove(fr, sq1, sq2) => {
+                goal.is_set(&sq2) && (is_rps(visible(fr)) || is_rps(visible(sq1)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::Layer;
+    #[cfg(feature = "alloc")]
+    use crate::random::{Rng, SplitMix64};
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn distances() {
+        let tests = [
+            // Solo pieces walk one row at a time
+            ("6/7/6/7/6/7/R-5 w 0 1", Square(0), Some(6)),
+            ("r-5/7/6/7/6/7/6 w 0 1", Square(39), Some(6)),
+            ("6/7/6/R-6/6/7/6 w 0 1", Square(19), Some(3)),
+            // Stacks can jump
+            ("6/7/6/7/6/7/RP5 w 0 1", Square(0), Some(3)),
+            ("6/7/6/7/6/7/RR5 w 0 1", Square(0), Some(3)),
+            // Already home
+            ("R-5/7/6/7/6/7/6 w 0 1", Square(39), Some(0)),
+            // Empty squares and lone wise have no distance
+            ("6/7/6/7/6/7/6 w 0 1", Square(0), None),
+            ("6/7/6/7/6/7/W-5 w 0 1", Square(0), None),
+            ("6/7/6/7/6/7/WW5 w 0 1", Square(0), None),
+            // Wise carrying an rps piece can still win
+            ("6/7/6/7/6/7/WR5 w 0 1", Square(0), Some(3)),
+            // Walled in by own pieces
+            ("6/7/6/7/6/R-R-5/R-R-4 w 0 1", Square(0), None),
+            // Capturing a blocker is allowed, capturing the wrong piece isn't
+            ("6/7/6/7/6/p-p-5/R-p-4 w 0 1", Square(0), None),
+            ("6/7/6/7/6/s-p-5/R-p-4 w 0 1", Square(0), Some(6)),
+        ];
+
+        for (fen, sq, dist) in tests {
+            println!("{} {}", fen, sq);
+            assert_eq!(Position::from_fen(fen).goal_distance(sq), dist);
+        }
+    }
+
+    #[test]
+    fn wise_on_rps() {
+        // Invalid, but it shouldn't take the program down
+        let sq = Square(0);
+        let bb = Bitboard::from_square(&sq);
+        let mut pos = Position::empty();
+        pos.sides[Side::White as usize] |= bb;
+        pos.pieces[Piece::Rock as usize][Layer::Lower as usize] |= bb;
+        pos.pieces[Piece::Wise as usize][Layer::Upper as usize] |= bb;
+        pos.refresh_mailbox();
+
+        assert_eq!(pos.goal_distance(sq), None);
+        assert_eq!(pos.min_goal_distance(Side::White), None);
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn min_distances() {
+        // No single piece can break through the opponent's back rows
+        let pos = Position::from_fen("startpos");
+        assert_eq!(pos.min_goal_distance(Side::White), None);
+        assert_eq!(pos.min_goal_distance(Side::Black), None);
+
+        let pos = Position::from_fen("6/7/6/7/R-5/7/r-WW4 b 0 1");
+        assert_eq!(pos.min_goal_distance(Side::White), Some(4));
+        assert_eq!(pos.min_goal_distance(Side::Black), Some(0));
+
+        let pos = Position::from_fen("6/7/6/7/6/7/WW5 w 0 1");
+        assert_eq!(pos.min_goal_distance(Side::White), None);
+        assert_eq!(pos.min_goal_distance(Side::Black), None);
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn threats() {
+        let tests = [
+            ("startpos", false, false),
+            // One step from the goal row, or stacking onto a wise on it
+            ("6/R-6/6/r-6/6/7/6 w 0 1", true, false),
+            ("W-r-4/R-6/6/r-6/6/7/6 w 0 1", true, false),
+            // Blocked by pieces that can't be captured
+            ("r-5/R-6/6/r-6/6/7/6 w 0 1", false, false),
+            ("s-5/R-6/6/r-6/6/7/6 w 0 1", true, false),
+            // Stacks jump the last two rows
+            ("6/7/RP5/r-6/6/7/6 w 0 1", true, false),
+            ("s-s-4/7/WR5/r-6/6/7/6 w 0 1", true, false),
+            ("p-p-4/7/WR5/r-6/6/7/6 w 0 1", false, false),
+            // A stack moves in and its top piece finishes the job
+            ("6/7/6/RP5r-/6/7/6 w 0 1", true, false),
+            // A piece stacks and the new stack jumps
+            ("6/7/R-P-4/6r-/6/7/6 w 0 1", true, false),
+            // Black threatens too
+            ("6/7/6/7/6/r-6/S-5 w 0 1", false, true),
+            ("6/7/6/7/6/w-6/S-5 w 0 1", false, false),
+        ];
+
+        for (fen, white, black) in tests {
+            let pos = Position::from_fen(fen);
+            assert_eq!(pos.has_goal_threat(Side::White), white, "{}", fen);
+            assert_eq!(pos.has_goal_threat(Side::Black), black, "{}", fen);
+            assert_eq!(pos.winning_moves().next().is_some(), white, "{}", fen);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn winning_random() {
+        let mut rng = SplitMix64::new(0);
+
+        // Debug builds are too slow for the full walk, cargo test --release runs it
+        let games = if cfg!(debug_assertions) { 50 } else { 1000 };
+
+        for game in 0..games {
+            let mut pos = if game % 2 == 0 {
+                Position::from_fen("startpos")
+            } else {
+                Position::shuffled_start(rng.next_u64())
+            };
+
+            // Keep the halfmove counter down so games play on until someone wins
+            while !pos.is_gameover() {
+                for side in [Side::White, Side::Black] {
+                    let mut pos = pos;
+                    pos.turn = side;
+
+                    let goal = goal_row(side);
+                    let expected: Vec<Mv> = pos
+                        .moves()
+                        .filter(|mv| {
+                            let npos = pos.after_move(mv);
+                            (npos.sides[side as usize] & npos.get_rps() & goal).is_occupied()
+                        })
+                        .collect();
+
+                    let found: Vec<Mv> = pos.winning_moves().collect();
+                    assert_eq!(found, expected, "{}", pos.get_fen());
+                    assert_eq!(pos.has_goal_threat(side), !expected.is_empty());
+                }
+
+                let moves = pos.legal_moves();
+                pos.makemove(&moves[rng.below(moves.len() as u32) as usize]);
+                pos.halfmoves = 0;
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::position::Position;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// 64 bit FNV-1a
+#[must_use]
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+impl Position {
+    // FNV-1a over the board, side to move and halfmove counter. The fullmove
+    // counter is left out since makemove never changes it.
+    #[must_use]
+    pub fn hash(&self) -> u64 {
+        let words = self
+            .sides
+            .iter()
+            .chain(self.pieces.iter().flatten())
+            .map(|bb| bb.0)
+            .chain([self.turn as u64, self.halfmoves as u64]);
+
+        // Two side boards, eight piece boards, the turn and the halfmoves
+        let mut bytes = [0; 12 * 8];
+        for (chunk, word) in bytes.chunks_exact_mut(8).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        fnv1a(&bytes)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_values() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn transpositions() {
+        let pos = Position::from_fen("startpos");
+        let play = |moves: &[&str]| {
+            let mut pos = pos;
+            for movestr in moves {
+                let mv = pos.moves().find(|mv| mv.to_string() == *movestr).unwrap();
+                pos.makemove(&mv);
+            }
+            pos
+        };
+
+        let a = play(&["b1c1", "f1e1", "b2c2", "f2e2"]);
+        let b = play(&["b2c2", "f2e2", "b1c1", "f1e1"]);
+        assert_eq!(a.get_fen(), b.get_fen());
+        assert_eq!(a.hash(), b.hash());
+
+        let c = play(&["b1c1", "f1e1", "b2c2", "f3e3"]);
+        assert_ne!(a.hash(), c.hash());
+
+        let mut d = a;
+        d.turn = !d.turn;
+        assert_ne!(a.hash(), d.hash());
+        d = a;
+        d.halfmoves += 1;
+        assert_ne!(a.hash(), d.hash());
+        d = a;
+        d.fullmoves += 1;
+        assert_eq!(a.hash(), d.hash());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     bitboard::{Bitboard, Bitmasks},
     position::Position,
+    result::MAX_HALFMOVES,
 };
 
 impl Position {
@@ -16,6 +17,6 @@ impl Position {
             | (white_rps & Bitboard(Bitmasks::BlackHome as u64)).is_occupied()
             | (black_rps & Bitboard(Bitmasks::WhiteHome as u64)).is_occupied()
         // Halfmoves
-            | (self.halfmoves >= 20)
+            | (self.halfmoves >= MAX_HALFMOVES)
     }
 }
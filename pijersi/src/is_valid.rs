@@ -50,6 +50,9 @@ impl Position {
         // Wise on RPS
         debug_assert!((upper_wise & lower_rps).is_empty());
 
+        // Mailbox out of sync
+        debug_assert_eq!(self.mailbox_mismatch(), None);
+
         true
     }
 }
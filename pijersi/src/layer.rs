@@ -1,4 +1,4 @@
-use std::{fmt, ops::Not};
+use core::{fmt, ops::Not};
 
 #[derive(Clone, Copy, PartialEq)]
 #[must_use]
@@ -10,7 +10,6 @@ pub enum Layer {
 impl Not for Layer {
     type Output = Self;
 
-    #[must_use]
     fn not(self) -> Self {
         match self {
             Layer::Lower => Layer::Upper,
@@ -1,15 +1,79 @@
-use crate::{mv::Mv, position::Position};
+use crate::{move_generator::NUM_STAGES, movelist::MoveList, mv::Mv, position::Position};
 
-impl Position {
-    #[must_use]
-    pub fn legal_moves(&self) -> Vec<Mv> {
-        let mut moves = vec![];
+// Generates the moves a stage at a time, so a search that stops early skips
+// the stages it never reaches
+pub struct Moves<'a> {
+    pos: &'a Position,
+    stage: usize,
+    list: MoveList,
+    idx: usize,
+}
+
+impl Iterator for Moves<'_> {
+    type Item = Mv;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&mv) = self.list.get(self.idx) {
+                self.idx += 1;
+                return Some(mv);
+            }
 
+            if self.stage == NUM_STAGES {
+                return None;
+            }
+
+            let list = &mut self.list;
+            list.clear();
+            self.pos.stage_generator(self.stage, &mut |mv| {
+                list.push(mv);
+                false
+            });
+            self.stage += 1;
+            self.idx = 0;
+        }
+    }
+}
+
+impl Position {
+    pub fn generate_moves(&self, moves: &mut MoveList) {
         self.move_generator(|mv| {
             moves.push(mv);
             false
         });
+    }
 
+    #[must_use]
+    pub fn legal_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        self.generate_moves(&mut moves);
         moves
     }
+
+    #[inline]
+    pub fn moves(&self) -> Moves<'_> {
+        Moves {
+            pos: self,
+            stage: 0,
+            list: MoveList::new(),
+            idx: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_match_legal_moves() {
+        let pos = Position::from_fen("startpos");
+        assert!(pos.moves().eq(pos.legal_moves()));
+
+        for mv in pos.legal_moves() {
+            let npos = pos.after_move(&mv);
+            assert!(npos.moves().eq(npos.legal_moves()), "{}", mv);
+        }
+    }
 }
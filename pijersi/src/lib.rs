@@ -1,17 +1,45 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod after_move;
 pub mod bitboard;
 pub mod bitboarditer;
+pub mod builder;
+pub mod check_move;
 pub mod count_moves;
+#[cfg(feature = "alloc")]
+pub mod epd;
+pub mod geometry;
+pub mod goal;
+pub mod hash;
 pub mod is_gameover;
 pub mod is_valid;
 pub mod layer;
 pub mod legal_moves;
 pub mod makemove;
+pub mod material;
+pub mod move_between;
 pub mod move_generator;
+pub mod movelist;
 pub mod mv;
+pub mod observer;
+pub mod packed;
 pub mod perft;
 pub mod position;
+pub mod random;
+#[cfg(all(feature = "alloc", any(test, feature = "reference")))]
+pub mod reference;
 pub mod result;
 pub mod set_fen;
+pub mod shuffled_start;
 pub mod side;
 pub mod square;
+pub mod stack;
+#[cfg(feature = "std")]
+pub mod tablebase;
+pub mod tables;
+#[cfg(feature = "alloc")]
+pub mod tree;
+pub mod validate;
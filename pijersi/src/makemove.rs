@@ -2,11 +2,31 @@ use crate::{
     bitboard::Bitboard,
     layer::Layer,
     mv::Mv,
+    observer::{MoveObserver, NoObserver},
     position::{Piece, Position},
+    square::Square,
+    stack::Stack,
 };
 
 impl Position {
-    pub fn makemove(&mut self, mv: &Mv) -> () {
+    pub fn makemove(&mut self, mv: &Mv) {
+        self.makemove_with(mv, &mut NoObserver);
+    }
+
+    // Reports the enemy stack on the square as removed, if there is one
+    #[inline]
+    fn observe_capture(&self, sq: Square, observer: &mut impl MoveObserver) {
+        if let Some(stack) = self.stack_on(sq).filter(|stack| stack.side != self.turn) {
+            observer.remove(stack.side, stack.bottom, Layer::Lower, sq);
+            if let Some(top) = stack.top {
+                observer.remove(stack.side, top, Layer::Upper, sq);
+            }
+        }
+    }
+
+    pub fn makemove_with(&mut self, mv: &Mv, observer: &mut impl MoveObserver) {
+        let us = self.turn;
+
         match mv {
             Mv::SoloMove(fr, to) => {
                 debug_assert_ne!(fr, to);
@@ -17,6 +37,10 @@ impl Position {
 
                 let piece = self.get_piece_on(Layer::Lower, *fr).unwrap();
 
+                self.observe_capture(*to, observer);
+                observer.remove(us, piece, Layer::Lower, *fr);
+                observer.add(us, piece, Layer::Lower, *to);
+
                 // Remove captured
                 self.sides[!self.turn as usize] &= !Bitboard::from_square(to);
                 self.pieces[Piece::Rock as usize][Layer::Lower as usize] &=
@@ -42,7 +66,11 @@ impl Position {
                 self.sides[self.turn as usize] ^= Bitboard::from_square(to);
                 self.pieces[piece as usize][Layer::Lower as usize] ^= Bitboard::from_square(to);
 
-                debug_assert!(self.get_side_on(*fr) == None);
+                // Update mailbox
+                self.mailbox[to.0 as usize] = self.mailbox[fr.0 as usize];
+                self.mailbox[fr.0 as usize] = 0;
+
+                debug_assert!(self.get_side_on(*fr).is_none());
                 debug_assert!(self.get_side_on(*to) == Some(self.turn));
                 debug_assert!(self.get_piece_on(Layer::Lower, *fr).is_none());
                 debug_assert!(self.get_piece_on(Layer::Upper, *fr).is_none());
@@ -62,6 +90,10 @@ impl Position {
                 debug_assert!(self.get_piece_on(Layer::Upper, *to).is_none());
 
                 let piece = self.get_piece_on(Layer::Lower, *fr).unwrap();
+                let target = self.get_piece_on(Layer::Lower, *to).unwrap();
+
+                observer.remove(us, piece, Layer::Lower, *fr);
+                observer.add(us, piece, Layer::Upper, *to);
 
                 // Remove piece
                 self.sides[self.turn as usize] ^= Bitboard::from_square(fr);
@@ -70,7 +102,15 @@ impl Position {
                 // Add piece
                 self.pieces[piece as usize][Layer::Upper as usize] ^= Bitboard::from_square(to);
 
-                debug_assert!(self.get_side_on(*fr) == None);
+                // Update mailbox
+                self.mailbox[fr.0 as usize] = 0;
+                self.mailbox[to.0 as usize] = Stack {
+                    top: Some(piece),
+                    ..Stack::solo(self.turn, target)
+                }
+                .to_code();
+
+                debug_assert!(self.get_side_on(*fr).is_none());
                 debug_assert!(self.get_side_on(*to) == Some(self.turn));
                 debug_assert!(self.get_piece_on(Layer::Lower, *fr).is_none());
                 debug_assert!(self.get_piece_on(Layer::Upper, *fr).is_none());
@@ -93,6 +133,12 @@ impl Position {
                 let piece1 = self.get_piece_on(Layer::Lower, *fr).unwrap();
                 let piece2 = self.get_piece_on(Layer::Lower, *sq1).unwrap();
 
+                observer.remove(us, piece1, Layer::Lower, *fr);
+                observer.remove(us, piece2, Layer::Lower, *sq1);
+                self.observe_capture(*sq2, observer);
+                observer.add(us, piece2, Layer::Lower, *sq2);
+                observer.add(us, piece1, Layer::Upper, *sq2);
+
                 // Remove piece1
                 self.sides[self.turn as usize] ^= Bitboard::from_square(fr);
                 self.pieces[piece1 as usize][Layer::Lower as usize] ^= Bitboard::from_square(fr);
@@ -123,7 +169,16 @@ impl Position {
                 self.pieces[piece1 as usize][Layer::Upper as usize] ^= Bitboard::from_square(sq2);
                 self.pieces[piece2 as usize][Layer::Lower as usize] ^= Bitboard::from_square(sq2);
 
-                debug_assert!(self.get_side_on(*sq1) == None);
+                // Update mailbox
+                self.mailbox[fr.0 as usize] = 0;
+                self.mailbox[sq1.0 as usize] = 0;
+                self.mailbox[sq2.0 as usize] = Stack {
+                    top: Some(piece1),
+                    ..Stack::solo(self.turn, piece2)
+                }
+                .to_code();
+
+                debug_assert!(self.get_side_on(*sq1).is_none());
                 debug_assert!(self.get_piece_on(Layer::Lower, *sq1).is_none());
                 debug_assert!(self.get_piece_on(Layer::Upper, *sq1).is_none());
                 debug_assert!(self.get_piece_on(Layer::Lower, *sq2).is_some());
@@ -142,6 +197,12 @@ impl Position {
                 let piece1 = self.get_piece_on(Layer::Lower, *fr).unwrap();
                 let piece2 = self.get_piece_on(Layer::Upper, *fr).unwrap();
 
+                self.observe_capture(*to, observer);
+                observer.remove(us, piece1, Layer::Lower, *fr);
+                observer.remove(us, piece2, Layer::Upper, *fr);
+                observer.add(us, piece1, Layer::Lower, *to);
+                observer.add(us, piece2, Layer::Upper, *to);
+
                 // Remove captured
                 self.sides[!self.turn as usize] &= !Bitboard::from_square(to);
                 self.pieces[Piece::Rock as usize][Layer::Lower as usize] &=
@@ -169,7 +230,11 @@ impl Position {
                 self.pieces[piece1 as usize][Layer::Lower as usize] ^= Bitboard::from_square(to);
                 self.pieces[piece2 as usize][Layer::Upper as usize] ^= Bitboard::from_square(to);
 
-                debug_assert!(self.get_side_on(*fr) == None);
+                // Update mailbox
+                self.mailbox[to.0 as usize] = self.mailbox[fr.0 as usize];
+                self.mailbox[fr.0 as usize] = 0;
+
+                debug_assert!(self.get_side_on(*fr).is_none());
                 debug_assert!(self.get_side_on(*to) == Some(self.turn));
                 debug_assert!(self.get_piece_on(Layer::Lower, *fr).is_none());
                 debug_assert!(self.get_piece_on(Layer::Upper, *fr).is_none());
@@ -196,6 +261,14 @@ impl Position {
                 };
 
                 let capture_2 = Bitboard::from_square(sq2) & self.get_them();
+                let target = self.get_piece_on(Layer::Lower, *sq2);
+
+                self.observe_capture(*sq1, observer);
+                self.observe_capture(*sq2, observer);
+                observer.remove(us, piece1, Layer::Lower, *fr);
+                observer.remove(us, piece2, Layer::Upper, *fr);
+                observer.add(us, piece1, Layer::Lower, *sq1);
+                observer.add(us, piece2, destack_layer, *sq2);
 
                 // Remove captured
                 self.sides[!self.turn as usize] &= !Bitboard::from_square(sq1);
@@ -237,6 +310,18 @@ impl Position {
                 self.sides[self.turn as usize] |= Bitboard::from_square(sq2);
                 self.pieces[piece2 as usize][destack_layer as usize] ^= Bitboard::from_square(sq2);
 
+                // Update mailbox
+                self.mailbox[fr.0 as usize] = 0;
+                self.mailbox[sq1.0 as usize] = Stack::solo(self.turn, piece1).to_code();
+                self.mailbox[sq2.0 as usize] = match destack_layer {
+                    Layer::Lower => Stack::solo(self.turn, piece2),
+                    Layer::Upper => Stack {
+                        top: Some(piece2),
+                        ..Stack::solo(self.turn, target.unwrap())
+                    },
+                }
+                .to_code();
+
                 debug_assert!(self.get_side_on(*sq1) == Some(self.turn));
                 debug_assert!(self.get_side_on(*sq2) == Some(self.turn));
                 debug_assert!(self.get_piece_on(Layer::Lower, *sq1).is_some());
@@ -254,6 +339,11 @@ impl Position {
                 debug_assert!(self.get_piece_on(Layer::Upper, *fr).is_some());
 
                 let piece = self.get_piece_on(Layer::Upper, *fr).unwrap();
+                let bottom = self.get_piece_on(Layer::Lower, *fr).unwrap();
+
+                self.observe_capture(*to, observer);
+                observer.remove(us, piece, Layer::Upper, *fr);
+                observer.add(us, piece, Layer::Lower, *to);
 
                 // Remove captured
                 self.sides[!self.turn as usize] &= !Bitboard::from_square(to);
@@ -279,6 +369,10 @@ impl Position {
                 self.sides[self.turn as usize] ^= Bitboard::from_square(to);
                 self.pieces[piece as usize][Layer::Lower as usize] ^= Bitboard::from_square(to);
 
+                // Update mailbox
+                self.mailbox[fr.0 as usize] = Stack::solo(self.turn, bottom).to_code();
+                self.mailbox[to.0 as usize] = Stack::solo(self.turn, piece).to_code();
+
                 debug_assert!(self.get_side_on(*fr) == Some(self.turn));
                 debug_assert!(self.get_side_on(*to) == Some(self.turn));
                 debug_assert!(self.get_piece_on(Layer::Lower, *fr).is_some());
@@ -299,6 +393,11 @@ impl Position {
                 debug_assert!(self.get_piece_on(Layer::Upper, *to).is_none());
 
                 let piece = self.get_piece_on(Layer::Upper, *fr).unwrap();
+                let bottom = self.get_piece_on(Layer::Lower, *fr).unwrap();
+                let target = self.get_piece_on(Layer::Lower, *to).unwrap();
+
+                observer.remove(us, piece, Layer::Upper, *fr);
+                observer.add(us, piece, Layer::Upper, *to);
 
                 // Remove piece
                 self.pieces[piece as usize][Layer::Upper as usize] ^= Bitboard::from_square(fr);
@@ -306,6 +405,14 @@ impl Position {
                 // Add piece
                 self.pieces[piece as usize][Layer::Upper as usize] ^= Bitboard::from_square(to);
 
+                // Update mailbox
+                self.mailbox[fr.0 as usize] = Stack::solo(self.turn, bottom).to_code();
+                self.mailbox[to.0 as usize] = Stack {
+                    top: Some(piece),
+                    ..Stack::solo(self.turn, target)
+                }
+                .to_code();
+
                 debug_assert!(self.get_side_on(*fr) == Some(self.turn));
                 debug_assert!(self.get_side_on(*to) == Some(self.turn));
                 debug_assert!(self.get_piece_on(Layer::Lower, *fr).is_some());
@@ -328,6 +435,13 @@ impl Position {
 
                 let piece1 = self.get_piece_on(Layer::Upper, *fr).unwrap();
                 let piece2 = self.get_piece_on(Layer::Lower, *sq1).unwrap();
+                let bottom = self.get_piece_on(Layer::Lower, *fr).unwrap();
+
+                observer.remove(us, piece1, Layer::Upper, *fr);
+                observer.remove(us, piece2, Layer::Lower, *sq1);
+                self.observe_capture(*sq2, observer);
+                observer.add(us, piece2, Layer::Lower, *sq2);
+                observer.add(us, piece1, Layer::Upper, *sq2);
 
                 // Remove captured
                 self.sides[!self.turn as usize] &= !Bitboard::from_square(sq2);
@@ -358,8 +472,17 @@ impl Position {
                 self.pieces[piece1 as usize][Layer::Upper as usize] ^= Bitboard::from_square(sq2);
                 self.pieces[piece2 as usize][Layer::Lower as usize] ^= Bitboard::from_square(sq2);
 
+                // Update mailbox
+                self.mailbox[fr.0 as usize] = Stack::solo(self.turn, bottom).to_code();
+                self.mailbox[sq1.0 as usize] = 0;
+                self.mailbox[sq2.0 as usize] = Stack {
+                    top: Some(piece1),
+                    ..Stack::solo(self.turn, piece2)
+                }
+                .to_code();
+
                 debug_assert!(self.get_side_on(*fr) == Some(self.turn));
-                debug_assert!(self.get_side_on(*sq1) == None);
+                debug_assert!(self.get_side_on(*sq1).is_none());
                 debug_assert!(self.get_side_on(*sq2) == Some(self.turn));
                 debug_assert!(self.get_piece_on(Layer::Lower, *fr).is_some());
                 debug_assert!(self.get_piece_on(Layer::Upper, *fr).is_none());
@@ -0,0 +1,162 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String};
+
+use crate::{
+    layer::Layer,
+    position::{Piece, Position},
+    side::Side,
+    stack::piece_char,
+};
+
+const PIECES: [Piece; 4] = [Piece::Rock, Piece::Paper, Piece::Scissors, Piece::Wise];
+
+// Per piece type counts, indexed by Piece
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Material {
+    pub lower: [i32; 4],
+    pub upper: [i32; 4],
+    pub solo: [i32; 4],
+    pub stacked: [i32; 4],
+}
+
+impl Material {
+    #[must_use]
+    pub fn count(&self, piece: Piece) -> i32 {
+        self.lower[piece as usize] + self.upper[piece as usize]
+    }
+
+    #[must_use]
+    pub fn total(&self) -> i32 {
+        self.lower.iter().sum::<i32>() + self.upper.iter().sum::<i32>()
+    }
+
+    #[must_use]
+    pub fn num_rps(&self) -> i32 {
+        self.total() - self.count(Piece::Wise)
+    }
+
+    #[must_use]
+    pub fn num_stacks(&self) -> i32 {
+        self.upper.iter().sum()
+    }
+
+    // Squares occupied, counting a stack once
+    #[must_use]
+    pub fn num_squares(&self) -> i32 {
+        self.lower.iter().sum()
+    }
+}
+
+// Piece letters in R, P, S, W order, eg. "RRPSW"
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for piece in PIECES {
+            for _ in 0..self.count(piece) {
+                write!(f, "{}", piece_char(Side::White, piece))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Position {
+    #[must_use]
+    pub fn material(&self, side: Side) -> Material {
+        let us = self.sides[side as usize];
+        let tall = self.get_tall();
+        let mut material = Material::default();
+
+        for piece in PIECES {
+            let lower = self.pieces[piece as usize][Layer::Lower as usize] & us;
+            let upper = self.pieces[piece as usize][Layer::Upper as usize] & us;
+
+            material.lower[piece as usize] = lower.count();
+            material.upper[piece as usize] = upper.count();
+            material.solo[piece as usize] = (lower & !tall).count();
+            material.stacked[piece as usize] = (lower & tall).count() + upper.count();
+        }
+
+        material
+    }
+
+    // Same as material(side).total() without the per piece counts, for evals
+    #[must_use]
+    pub fn num_pieces(&self, side: Side) -> i32 {
+        let us = self.sides[side as usize];
+        (self.get_lower() & us).count() + (self.get_upper() & us).count()
+    }
+
+    // White's pieces then black's, eg. "RRPSW vs RPS"
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn material_signature(&self) -> String {
+        format!(
+            "{} vs {}",
+            self.material(Side::White),
+            self.material(Side::Black)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn startpos() {
+        let pos = Position::from_fen("startpos");
+
+        for side in [Side::White, Side::Black] {
+            let material = pos.material(side);
+            assert_eq!(material.lower, [4, 4, 4, 1]);
+            assert_eq!(material.upper, [0, 0, 0, 1]);
+            assert_eq!(material.solo, [4, 4, 4, 0]);
+            assert_eq!(material.stacked, [0, 0, 0, 2]);
+            assert_eq!(material.total(), 14);
+            assert_eq!(material.num_rps(), 12);
+            assert_eq!(material.num_stacks(), 1);
+            assert_eq!(material.num_squares(), 13);
+        }
+
+        assert_eq!(pos.material_signature(), "RRRRPPPPSSSSWW vs RRRRPPPPSSSSWW");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn signatures() {
+        let tests = [
+            ("6/7/6/7/6/7/6 w 0 1", " vs "),
+            ("6/7/6/3R-r-2/6/7/6 w 0 1", "R vs R"),
+            ("5ps/7/6/7/6/7/WW5 b 0 1", "WW vs PS"),
+            ("s-4ww/7/6/7/6/7/RP4W- w 0 1", "RPW vs SWW"),
+        ];
+
+        for (fen, signature) in tests {
+            let pos = Position::from_fen(fen);
+            assert_eq!(pos.material_signature(), signature);
+
+            for side in [Side::White, Side::Black] {
+                assert_eq!(pos.num_pieces(side), pos.material(side).total());
+            }
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn stacks() {
+        let pos = Position::from_fen("6/7/6/7/6/7/RP5 w 0 1");
+        let material = pos.material(Side::White);
+
+        assert_eq!(material.count(Piece::Rock), 1);
+        assert_eq!(material.count(Piece::Paper), 1);
+        assert_eq!(material.lower, [1, 0, 0, 0]);
+        assert_eq!(material.upper, [0, 1, 0, 0]);
+        assert_eq!(material.solo, [0, 0, 0, 0]);
+        assert_eq!(material.stacked, [1, 1, 0, 0]);
+        assert_eq!(pos.material(Side::Black), Material::default());
+    }
+}
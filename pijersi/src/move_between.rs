@@ -0,0 +1,139 @@
+use core::fmt;
+
+use crate::{movelist::MoveList, mv::Mv, position::Position};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoveBetweenError {
+    Unreachable,
+    // How many legal moves lead to the target
+    Ambiguous(usize),
+}
+
+impl fmt::Display for MoveBetweenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveBetweenError::Unreachable => write!(f, "no legal move reaches the position"),
+            MoveBetweenError::Ambiguous(n) => write!(f, "{} legal moves reach the position", n),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MoveBetweenError {}
+
+impl Position {
+    // Every legal move that turns this board into the target's. Stacks of two
+    // identical pieces can reach the same board in more than one way.
+    #[must_use]
+    pub fn moves_between(&self, target: &Position) -> MoveList {
+        let mut moves = MoveList::new();
+        for mv in self.moves() {
+            if self.after_move(&mv).same_board(target) {
+                moves.push(mv);
+            }
+        }
+        moves
+    }
+
+    // The one legal move that leads to the target
+    pub fn move_between(&self, target: &Position) -> Result<Mv, MoveBetweenError> {
+        match *self.moves_between(target) {
+            [mv] => Ok(mv),
+            [] => Err(MoveBetweenError::Unreachable),
+            ref moves => Err(MoveBetweenError::Ambiguous(moves.len())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{Rng, SplitMix64};
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn between() {
+        let tests = [
+            ("startpos", "b1c1", 1),
+            ("startpos", "a1b1", 1),
+            ("startpos", "b4c4c3", 2),
+            ("startpos", "b4b4c4", 2),
+            ("6/1wwr-4/2R-s-2/1RRp-P-1WW1/2S-3/7/6 w 0 1", "d2c1c2", 2),
+        ];
+
+        for (fen, movestr, count) in tests {
+            let pos = Position::from_fen(fen);
+            let mv = pos.moves().find(|mv| mv.to_string() == movestr).unwrap();
+            let npos = pos.after_move(&mv);
+
+            let found = pos.moves_between(&npos);
+            assert_eq!(found.len(), count, "{} {}", fen, movestr);
+            assert!(found.contains(&mv));
+            let expected = match count {
+                1 => Ok(mv),
+                n => Err(MoveBetweenError::Ambiguous(n)),
+            };
+            assert_eq!(pos.move_between(&npos), expected);
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn unreachable() {
+        let pos = Position::from_fen("startpos");
+        let tests = [
+            "startpos",
+            "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/6/P-S-R-WWS-R-P-/R-P-S-R-P-S- b 0 1",
+            "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/P-5/1S-R-WWS-R-P-/R-P-S-R-P-S- w 1 1",
+        ];
+
+        for fen in tests {
+            assert!(
+                pos.moves_between(&Position::from_fen(fen)).is_empty(),
+                "{}",
+                fen
+            );
+            assert_eq!(
+                pos.move_between(&Position::from_fen(fen)),
+                Err(MoveBetweenError::Unreachable)
+            );
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn counters() {
+        let pos = Position::from_fen("startpos");
+        let mut target = Position::from_fen(
+            "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/P-5/1S-R-WWS-R-P-/R-P-S-R-P-S- b 0 1",
+        );
+        let mv = pos.move_between(&target).unwrap();
+        assert_eq!(mv.to_string(), "b1c1");
+
+        target.halfmoves = 7;
+        target.fullmoves = 30;
+        assert_eq!(pos.move_between(&target), Ok(mv));
+    }
+
+    #[test]
+    fn random_games() {
+        let mut rng = SplitMix64::new(0);
+
+        for _ in 0..20 {
+            let mut pos = Position::from_fen("startpos");
+            while !pos.is_gameover() {
+                let moves = pos.legal_moves();
+                let mv = moves[rng.below(moves.len() as u32) as usize];
+                let npos = pos.after_move(&mv);
+
+                let found = pos.moves_between(&npos);
+                assert!(found.contains(&mv));
+                for other in found.iter() {
+                    assert!(pos.after_move(other).same_board(&npos));
+                }
+
+                pos = npos;
+            }
+        }
+    }
+}
@@ -2,8 +2,10 @@ use crate::{
     bitboard::Bitboard,
     mv::Mv,
     position::{Piece, Position},
+    tables::{adjacent, jumps},
 };
 
+#[allow(clippy::too_many_arguments)]
 fn get_moves(
     us: Bitboard,
     them: Bitboard,
@@ -18,7 +20,7 @@ fn get_moves(
 
     // SoloMove
     for solo in piece_mask & short {
-        for mv in Bitboard::from_square(&solo).adjacent() & (empty | capturable) {
+        for mv in adjacent(solo) & (empty | capturable) {
             if func(Mv::SoloMove(solo, mv)) {
                 return true;
             }
@@ -27,7 +29,7 @@ fn get_moves(
 
     // SoloStack
     for solo in piece_mask & short {
-        for stack in Bitboard::from_square(&solo).adjacent() & us & short & stackable {
+        for stack in adjacent(solo) & us & short & stackable {
             if func(Mv::SoloStack(solo, stack)) {
                 return true;
             }
@@ -36,10 +38,8 @@ fn get_moves(
 
     // SoloStackMove
     for solo in piece_mask & short {
-        for stack in Bitboard::from_square(&solo).adjacent() & us & short & stackable {
-            let bb = Bitboard::from_square(&stack);
-
-            let dist1 = bb.adjacent() & (empty | capturable | Bitboard::from_square(&solo));
+        for stack in adjacent(solo) & us & short & stackable {
+            let dist1 = adjacent(stack) & (empty | capturable | Bitboard::from_square(&solo));
             for mv in dist1 {
                 if func(Mv::SoloStackMove(solo, stack, mv)) {
                     return true;
@@ -47,7 +47,7 @@ fn get_moves(
             }
 
             let blockers = (us | them) ^ Bitboard::from_square(&solo);
-            let dist2 = bb.doubles(blockers) & (empty | capturable);
+            let dist2 = jumps(stack, blockers) & (empty | capturable);
             for mv in dist2 {
                 if func(Mv::SoloStackMove(solo, stack, mv)) {
                     return true;
@@ -58,16 +58,14 @@ fn get_moves(
 
     // StackMove
     for stack in piece_mask & tall {
-        let bb = Bitboard::from_square(&stack);
-
-        let dist1 = bb.adjacent() & (empty | capturable);
+        let dist1 = adjacent(stack) & (empty | capturable);
         for mv in dist1 {
             if func(Mv::StackMove(stack, mv)) {
                 return true;
             }
         }
 
-        let dist2 = bb.doubles(us | them) & (empty | capturable);
+        let dist2 = jumps(stack, us | them) & (empty | capturable);
         for mv in dist2 {
             if func(Mv::StackMove(stack, mv)) {
                 return true;
@@ -79,21 +77,18 @@ fn get_moves(
     for stack in piece_mask & tall {
         let bb = Bitboard::from_square(&stack);
 
-        let dist1 = bb.adjacent() & (empty | capturable);
+        let dist1 = adjacent(stack) & (empty | capturable);
         for mv in dist1 {
-            for destack in
-                Bitboard::from_square(&mv).adjacent() & (empty | stackable | capturable | bb)
-            {
+            for destack in adjacent(mv) & (empty | stackable | capturable | bb) {
                 if func(Mv::StackMoveDestack(stack, mv, destack)) {
                     return true;
                 }
             }
         }
 
-        let dist2 = bb.doubles(us | them) & (empty | capturable);
+        let dist2 = jumps(stack, us | them) & (empty | capturable);
         for mv in dist2 {
-            for destack in Bitboard::from_square(&mv).adjacent() & (empty | stackable | capturable)
-            {
+            for destack in adjacent(mv) & (empty | stackable | capturable) {
                 if func(Mv::StackMoveDestack(stack, mv, destack)) {
                     return true;
                 }
@@ -103,7 +98,7 @@ fn get_moves(
 
     // StackDestack
     for stack in piece_mask & tall {
-        for destack in Bitboard::from_square(&stack).adjacent() & (empty | capturable) {
+        for destack in adjacent(stack) & (empty | capturable) {
             if func(Mv::StackDestack(stack, destack)) {
                 return true;
             }
@@ -112,7 +107,7 @@ fn get_moves(
 
     // StackStack
     for stack1 in piece_mask & tall {
-        for stack2 in Bitboard::from_square(&stack1).adjacent() & us & short & stackable {
+        for stack2 in adjacent(stack1) & us & short & stackable {
             if func(Mv::StackStack(stack1, stack2)) {
                 return true;
             }
@@ -121,17 +116,15 @@ fn get_moves(
 
     // StackStackMove
     for stack1 in piece_mask & tall {
-        for stack2 in Bitboard::from_square(&stack1).adjacent() & us & short & stackable {
-            let bb = Bitboard::from_square(&stack2);
-
-            let dist1 = bb.adjacent() & (empty | capturable);
+        for stack2 in adjacent(stack1) & us & short & stackable {
+            let dist1 = adjacent(stack2) & (empty | capturable);
             for mv in dist1 {
                 if func(Mv::StackStackMove(stack1, stack2, mv)) {
                     return true;
                 }
             }
 
-            let dist2 = bb.doubles(us | them) & (empty | capturable);
+            let dist2 = jumps(stack2, us | them) & (empty | capturable);
             for mv in dist2 {
                 if func(Mv::StackStackMove(stack1, stack2, mv)) {
                     return true;
@@ -143,62 +136,58 @@ fn get_moves(
     false
 }
 
-impl Position {
-    pub fn move_generator(&self, mut func: impl FnMut(Mv) -> bool) -> () {
-        // Rock > Scissors
-        if get_moves(
-            self.get_us(),
-            self.get_them(),
-            self.get_short(),
-            self.get_tall(),
-            self.get_visible(Piece::Rock) & self.get_us(),
-            self.get_us() & self.get_short(),
-            self.get_visible(Piece::Scissors) & self.get_them(),
-            &mut func,
-        ) {
-            return;
-        }
-
-        // Paper > Rock
-        if get_moves(
-            self.get_us(),
-            self.get_them(),
-            self.get_short(),
-            self.get_tall(),
-            self.get_visible(Piece::Paper) & self.get_us(),
-            self.get_us() & self.get_short(),
-            self.get_visible(Piece::Rock) & self.get_them(),
-            &mut func,
-        ) {
-            return;
-        }
+// Moves come in one stage per piece type that can move, in the order rock,
+// paper, scissors, wise
+pub const NUM_STAGES: usize = 4;
 
-        // Scissors > Paper
-        if get_moves(
+impl Position {
+    // Calls func with the moves of one stage until it returns true, returns
+    // whether it did
+    pub fn stage_generator(&self, stage: usize, func: &mut impl FnMut(Mv) -> bool) -> bool {
+        let (piece, stackable, capturable) = match stage {
+            // Rock > Scissors
+            0 => (
+                Piece::Rock,
+                self.get_us() & self.get_short(),
+                self.get_visible(Piece::Scissors) & self.get_them(),
+            ),
+            // Paper > Rock
+            1 => (
+                Piece::Paper,
+                self.get_us() & self.get_short(),
+                self.get_visible(Piece::Rock) & self.get_them(),
+            ),
+            // Scissors > Paper
+            2 => (
+                Piece::Scissors,
+                self.get_us() & self.get_short(),
+                self.get_visible(Piece::Paper) & self.get_them(),
+            ),
+            // Wise > All
+            _ => (
+                Piece::Wise,
+                self.get_us() & self.get_short() & self.get_wise(),
+                Bitboard::empty(),
+            ),
+        };
+
+        get_moves(
             self.get_us(),
             self.get_them(),
             self.get_short(),
             self.get_tall(),
-            self.get_visible(Piece::Scissors) & self.get_us(),
-            self.get_us() & self.get_short(),
-            self.get_visible(Piece::Paper) & self.get_them(),
-            &mut func,
-        ) {
-            return;
-        }
+            self.get_visible(piece) & self.get_us(),
+            stackable,
+            capturable,
+            func,
+        )
+    }
 
-        // Wise > All
-        if get_moves(
-            self.get_us(),
-            self.get_them(),
-            self.get_short(),
-            self.get_tall(),
-            self.get_visible(Piece::Wise) & self.get_us(),
-            self.get_us() & self.get_short() & self.get_wise(),
-            Bitboard::empty(),
-            &mut func,
-        ) {
-            return;
+    pub fn move_generator(&self, mut func: impl FnMut(Mv) -> bool) {
+        for stage in 0..NUM_STAGES {
+            if self.stage_generator(stage, &mut func) {
+                return;
+            }
         }
     }
 }
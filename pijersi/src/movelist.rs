@@ -0,0 +1,126 @@
+use core::{mem::MaybeUninit, ops::Deref};
+
+use crate::mv::Mv;
+
+// Loose upper bound on the number of legal moves in any position
+pub const MAX_MOVES: usize = 1024;
+
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [MaybeUninit<Mv>; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            moves: [const { MaybeUninit::uninit() }; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Mv) {
+        debug_assert!(self.len < MAX_MOVES);
+        self.moves[self.len].write(mv);
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    #[must_use]
+    pub fn as_slice(&self) -> &[Mv] {
+        // SAFETY: the first len entries have all been written by push
+        unsafe { core::slice::from_raw_parts(self.moves.as_ptr() as *const Mv, self.len) }
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Mv];
+
+    fn deref(&self) -> &[Mv] {
+        self.as_slice()
+    }
+}
+
+pub struct MoveListIter {
+    list: MoveList,
+    idx: usize,
+}
+
+impl Iterator for MoveListIter {
+    type Item = Mv;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mv = self.list.get(self.idx).copied();
+        self.idx += mv.is_some() as usize;
+        mv
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.list.len - self.idx;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for MoveListIter {}
+
+impl IntoIterator for MoveList {
+    type Item = Mv;
+    type IntoIter = MoveListIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MoveListIter { list: self, idx: 0 }
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Mv;
+    type IntoIter = core::slice::Iter<'a, Mv>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::Square;
+
+    #[test]
+    fn push() {
+        let mut list = MoveList::new();
+        assert!(list.is_empty());
+
+        list.push(Mv::SoloMove(Square(0), Square(1)));
+        list.push(Mv::StackMove(Square(2), Square(3)));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[1].to_string(), "a3a4a4");
+
+        list.clear();
+        assert!(list.is_empty());
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn iter() {
+        use crate::position::Position;
+        let pos = Position::from_fen("startpos");
+        let moves = pos.legal_moves();
+        let strings: Vec<String> = moves.iter().map(|mv| mv.to_string()).collect();
+
+        assert_eq!(moves.len(), 186);
+        assert_eq!(moves.into_iter().len(), 186);
+        assert_eq!(pos.moves().count(), 186);
+        assert!(pos.moves().map(|mv| mv.to_string()).eq(strings));
+    }
+}
@@ -1,8 +1,8 @@
-use std::fmt;
+use core::fmt;
 
 use crate::square::Square;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum Mv {
     SoloMove(Square, Square),
     SoloStack(Square, Square),
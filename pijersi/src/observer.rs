@@ -0,0 +1,96 @@
+use crate::{layer::Layer, position::Piece, side::Side, square::Square};
+
+// Told about every piece makemove_with takes off or puts on the board, for
+// evaluations kept up to date incrementally. A piece that moves or changes
+// layer is removed from where it was and added where it ends up. Removals are
+// all reported before additions.
+pub trait MoveObserver {
+    fn add(&mut self, side: Side, piece: Piece, layer: Layer, sq: Square);
+    fn remove(&mut self, side: Side, piece: Piece, layer: Layer, sq: Square);
+}
+
+// Ignores everything, plain makemove uses it so it costs nothing extra
+pub struct NoObserver;
+
+impl MoveObserver for NoObserver {
+    #[inline(always)]
+    fn add(&mut self, _side: Side, _piece: Piece, _layer: Layer, _sq: Square) {}
+
+    #[inline(always)]
+    fn remove(&mut self, _side: Side, _piece: Piece, _layer: Layer, _sq: Square) {}
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::NUM_SQUARES,
+        position::Position,
+        random::{Rng, SplitMix64},
+    };
+
+    type Board = [[Option<(Side, Piece)>; 2]; NUM_SQUARES];
+
+    fn board(pos: &Position) -> Board {
+        let mut board = [[None; 2]; NUM_SQUARES];
+        for (sq, layers) in board.iter_mut().enumerate() {
+            if let Some(stack) = pos.stack_on(Square(sq as u8)) {
+                layers[Layer::Lower as usize] = Some((stack.side, stack.bottom));
+                layers[Layer::Upper as usize] = stack.top.map(|top| (stack.side, top));
+            }
+        }
+        board
+    }
+
+    // Replays the events on its own copy of the board
+    struct Replay {
+        board: Board,
+        adding: bool,
+    }
+
+    impl MoveObserver for Replay {
+        fn add(&mut self, side: Side, piece: Piece, layer: Layer, sq: Square) {
+            self.adding = true;
+            let slot = &mut self.board[sq.0 as usize][layer as usize];
+            assert_eq!(*slot, None, "add to occupied {}", sq);
+            *slot = Some((side, piece));
+        }
+
+        fn remove(&mut self, side: Side, piece: Piece, layer: Layer, sq: Square) {
+            assert!(!self.adding, "remove after add on {}", sq);
+            let slot = &mut self.board[sq.0 as usize][layer as usize];
+            assert_eq!(*slot, Some((side, piece)), "remove from {}", sq);
+            *slot = None;
+        }
+    }
+
+    #[test]
+    fn replayed() {
+        let mut rng = SplitMix64::new(0);
+
+        for game in 0..30 {
+            let mut pos = if game % 2 == 0 {
+                Position::from_fen("startpos")
+            } else {
+                Position::shuffled_start(rng.next_u64())
+            };
+
+            while !pos.is_gameover() {
+                for mv in pos.moves() {
+                    let mut replay = Replay {
+                        board: board(&pos),
+                        adding: false,
+                    };
+                    let mut npos = pos;
+                    npos.makemove_with(&mv, &mut replay);
+
+                    assert_eq!(replay.board, board(&npos), "{} {}", pos.get_fen(), mv);
+                    assert_eq!(npos.get_fen(), pos.after_move(&mv).get_fen());
+                }
+
+                let moves = pos.legal_moves();
+                pos.makemove(&moves[rng.below(moves.len() as u32) as usize]);
+            }
+        }
+    }
+}
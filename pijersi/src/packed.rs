@@ -0,0 +1,315 @@
+// Fixed size binary positions for datasets. Bits are written from the lowest
+// bit of the first byte up:
+//   occupancy, a bit per square
+//   the side to move
+//   the halfmove clock as a byte
+//   a stack for each occupied square in square order
+// A stack is its bottom and top pieces as a nibble, then a bit for whether it
+// has a top and a bit for its side, with the top bits left at zero for a solo
+// piece. Anything after the last stack is zero too, so every position has
+// exactly one encoding. The fullmove counter isn't kept since makemove never
+// changes it.
+
+use core::fmt;
+
+use crate::{
+    bitboard::Bitboard,
+    builder::PositionBuilder,
+    geometry::NUM_SQUARES,
+    position::{Piece, Position},
+    side::Side,
+    square::Square,
+    validate::{max_pieces, PositionError},
+};
+
+const PIECES: [Piece; 4] = [Piece::Rock, Piece::Paper, Piece::Scissors, Piece::Wise];
+
+// Every piece of both sides on a square of its own
+const MAX_STACKS: usize = 2
+    * (max_pieces(Piece::Rock)
+        + max_pieces(Piece::Paper)
+        + max_pieces(Piece::Scissors)
+        + max_pieces(Piece::Wise)) as usize;
+
+const STACK_BITS: usize = 6;
+const PACKED_BITS: usize = NUM_SQUARES + 1 + 8 + MAX_STACKS * STACK_BITS;
+pub const PACKED_SIZE: usize = PACKED_BITS.div_ceil(8);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UnpackError {
+    WrongLength(usize),
+    TooManyStacks(i32),
+    StrayTop(Square),
+    NonZeroPadding,
+    BadPosition(PositionError),
+}
+
+impl fmt::Display for UnpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnpackError::WrongLength(n) => {
+                write!(f, "expected {} bytes but got {}", PACKED_SIZE, n)
+            }
+            UnpackError::TooManyStacks(n) => write!(f, "{} stacks is too many", n),
+            UnpackError::StrayTop(sq) => write!(f, "solo piece on {} has top bits set", sq),
+            UnpackError::NonZeroPadding => write!(f, "padding after the stacks isn't zero"),
+            UnpackError::BadPosition(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnpackError {}
+
+impl From<PositionError> for UnpackError {
+    fn from(err: PositionError) -> Self {
+        UnpackError::BadPosition(err)
+    }
+}
+
+struct Writer {
+    bytes: [u8; PACKED_SIZE],
+    bit: usize,
+}
+
+impl Writer {
+    fn write(&mut self, value: u64, bits: usize) {
+        for i in 0..bits {
+            let bit = self.bit + i;
+            self.bytes[bit / 8] |= ((value >> i & 1) as u8) << (bit % 8);
+        }
+        self.bit += bits;
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    bit: usize,
+}
+
+impl Reader<'_> {
+    fn read(&mut self, bits: usize) -> u64 {
+        let mut value = 0;
+        for i in 0..bits {
+            let bit = self.bit + i;
+            value |= ((self.bytes[bit / 8] >> (bit % 8) & 1) as u64) << i;
+        }
+        self.bit += bits;
+        value
+    }
+
+    fn rest_is_zero(&self) -> bool {
+        let (byte, bit) = (self.bit / 8, self.bit % 8);
+        self.bytes[byte] >> bit == 0 && self.bytes[byte + 1..].iter().all(|&b| b == 0)
+    }
+}
+
+impl Position {
+    // The position has to be valid and its halfmove clock has to fit in a
+    // byte, the game was drawn long before then anyway. The fullmove counter
+    // is dropped, so it doesn't survive a round trip.
+    #[must_use]
+    pub fn to_packed(&self) -> [u8; PACKED_SIZE] {
+        debug_assert!(self.validate().is_ok());
+        debug_assert!((0..=u8::MAX as i32).contains(&self.halfmoves));
+
+        let occupied = self.get_occupied();
+        assert!(occupied.count() as usize <= MAX_STACKS);
+
+        let mut writer = Writer {
+            bytes: [0; PACKED_SIZE],
+            bit: 0,
+        };
+        writer.write(occupied.0, NUM_SQUARES);
+        writer.write(self.turn as u64, 1);
+        writer.write(self.halfmoves.clamp(0, u8::MAX as i32) as u64, 8);
+
+        for sq in occupied {
+            let stack = self.stack_on(sq).unwrap();
+            let top = stack.top.map_or(0, |piece| 0x10 | piece as u64);
+            let code = (stack.side as u64) << 5 | top | (stack.bottom as u64) << 2;
+            writer.write(code, STACK_BITS);
+        }
+
+        writer.bytes
+    }
+
+    // The fullmove counter always comes back as 1
+    pub fn from_packed(bytes: &[u8]) -> Result<Position, UnpackError> {
+        if bytes.len() != PACKED_SIZE {
+            return Err(UnpackError::WrongLength(bytes.len()));
+        }
+
+        let mut reader = Reader { bytes, bit: 0 };
+        let occupied = Bitboard(reader.read(NUM_SQUARES));
+        if occupied.count() as usize > MAX_STACKS {
+            return Err(UnpackError::TooManyStacks(occupied.count()));
+        }
+
+        let turn = match reader.read(1) {
+            0 => Side::White,
+            _ => Side::Black,
+        };
+        let halfmoves = reader.read(8) as i32;
+        let mut builder = PositionBuilder::new().turn(turn).halfmoves(halfmoves);
+
+        for sq in occupied {
+            let code = reader.read(STACK_BITS);
+            let side = match code >> 5 {
+                0 => Side::White,
+                _ => Side::Black,
+            };
+            let bottom = PIECES[(code >> 2 & 3) as usize];
+            let top = match code & 0x10 {
+                0 if code & 3 != 0 => return Err(UnpackError::StrayTop(sq)),
+                0 => None,
+                _ => Some(PIECES[(code & 3) as usize]),
+            };
+            builder = builder.place(sq, side, bottom, top);
+        }
+
+        if !reader.rest_is_zero() {
+            return Err(UnpackError::NonZeroPadding);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{Rng, SplitMix64};
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn size() {
+        assert_eq!(MAX_STACKS, 28);
+        assert_eq!(PACKED_SIZE, 28);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn round_trip() {
+        let mut rng = SplitMix64::new(0);
+
+        for game in 0..200 {
+            let mut pos = if game % 2 == 0 {
+                Position::from_fen("startpos")
+            } else {
+                Position::shuffled_start(rng.next_u64())
+            };
+
+            loop {
+                let packed = pos.to_packed();
+                let unpacked = Position::from_packed(&packed).unwrap();
+                assert_eq!(unpacked.get_fen(), pos.get_fen());
+
+                if pos.is_gameover() {
+                    break;
+                }
+                let moves = pos.legal_moves();
+                pos.makemove(&moves[rng.below(moves.len() as u32) as usize]);
+            }
+        }
+
+        let tests = [
+            "6/7/6/7/6/7/6 w 0 1",
+            "ww5/7/6/7/6/7/WR5 b 19 1",
+            "1sp1rsp-1/p-2wwr-1pr/5s-/rs3R-2/1W-W-P-2/P-S-RP1S-2/R-1S-RP1S- w 255 1",
+        ];
+        for fen in tests {
+            let pos = Position::from_fen(fen);
+            let unpacked = Position::from_packed(&pos.to_packed()).unwrap();
+            assert_eq!(unpacked.get_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn random_bytes() {
+        let mut rng = SplitMix64::new(1);
+        let mut valid = 0;
+
+        for _ in 0..10000 {
+            // Mostly well formed, with the odd stray bit
+            let occupied = rng.next_u64() & rng.next_u64() & rng.next_u64() & rng.next_u64();
+            let occupied = Bitboard(occupied) & Bitboard::all();
+            let mut writer = Writer {
+                bytes: [0; PACKED_SIZE],
+                bit: 0,
+            };
+            writer.write(occupied.0, NUM_SQUARES);
+            writer.write(rng.next_u64(), 9);
+            for _ in occupied {
+                let mut code = rng.next_u64() & 0x3f;
+                if rng.below(8) != 0 && code & 0x10 == 0 {
+                    code &= !3;
+                }
+                writer.write(code, STACK_BITS);
+            }
+            if rng.below(8) == 0 {
+                let bit = writer.bit + rng.below((PACKED_SIZE * 8 - writer.bit) as u32) as usize;
+                writer.bytes[bit / 8] |= 1 << (bit % 8);
+            }
+
+            // Anything that decodes is the one encoding of its position
+            if let Ok(pos) = Position::from_packed(&writer.bytes) {
+                valid += 1;
+                assert_eq!(pos.to_packed(), writer.bytes);
+            }
+        }
+
+        assert!(valid > 100);
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn errors() {
+        let packed = Position::from_fen("startpos").to_packed();
+        assert_eq!(
+            Position::from_packed(&packed[1..]).err(),
+            Some(UnpackError::WrongLength(PACKED_SIZE - 1))
+        );
+
+        let mut full = [0; PACKED_SIZE];
+        full[..4].fill(0xff);
+        assert_eq!(
+            Position::from_packed(&full).err(),
+            Some(UnpackError::TooManyStacks(32))
+        );
+
+        // White wise on a white rock on a1
+        let mut writer = Writer {
+            bytes: [0; PACKED_SIZE],
+            bit: 0,
+        };
+        writer.write(1, NUM_SQUARES + 9);
+        writer.write(0x10 | Piece::Wise as u64, STACK_BITS);
+        assert_eq!(
+            Position::from_packed(&writer.bytes).err(),
+            Some(UnpackError::BadPosition(PositionError::WiseOnRps(Square(
+                0
+            ))))
+        );
+
+        // Bits that don't change the position
+        let mut stray = packed;
+        stray[PACKED_SIZE - 1] |= 0x80;
+        assert_eq!(
+            Position::from_packed(&stray).err(),
+            Some(UnpackError::NonZeroPadding)
+        );
+
+        let mut writer = Writer {
+            bytes: [0; PACKED_SIZE],
+            bit: 0,
+        };
+        writer.write(1, NUM_SQUARES + 9);
+        writer.write(Piece::Paper as u64, STACK_BITS);
+        assert_eq!(
+            Position::from_packed(&writer.bytes).err(),
+            Some(UnpackError::StrayTop(Square(0)))
+        );
+    }
+}
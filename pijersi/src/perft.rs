@@ -8,8 +8,7 @@ impl Position {
         } else if self.is_gameover() {
             0
         } else if depth == 1 {
-            self.count_moves() as u64
-            // self.count_moves_sneaky()
+            self.count_moves_sneaky() as u64
         } else {
             let mut nodes = 0u64;
 
@@ -1,11 +1,20 @@
-use std::fmt;
+use core::fmt;
 
-use crate::{bitboard::Bitboard, layer::Layer, side::Side, square::Square};
+use crate::{
+    bitboard::Bitboard,
+    geometry::{NUM_SQUARES, SHAPE},
+    layer::Layer,
+    side::Side,
+    square::Square,
+    stack::{piece_char, Stack},
+};
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Position {
     pub sides: [Bitboard; 2],
     pub pieces: [[Bitboard; 2]; 4],
+    // Packed stack code per square, mirrors the bitboards
+    pub mailbox: [u8; NUM_SQUARES],
     pub turn: Side,
     pub halfmoves: i32,
     pub fullmoves: i32,
@@ -31,6 +40,7 @@ impl Position {
                 [Bitboard::empty(), Bitboard::empty()],
                 [Bitboard::empty(), Bitboard::empty()],
             ],
+            mailbox: [0; NUM_SQUARES],
             turn: Side::White,
             halfmoves: 0,
             fullmoves: 1,
@@ -40,7 +50,7 @@ impl Position {
     #[must_use]
     pub fn from_fen(fen: &str) -> Self {
         let mut pos = Self::empty();
-        pos.set_fen(&fen);
+        pos.set_fen(fen);
         pos
     }
 
@@ -132,72 +142,63 @@ impl Position {
 
     #[must_use]
     pub fn get_piece_on(&self, layer: Layer, sq: Square) -> Option<Piece> {
-        if self.pieces[Piece::Rock as usize][layer as usize].is_set(&sq) {
-            Some(Piece::Rock)
-        } else if self.pieces[Piece::Paper as usize][layer as usize].is_set(&sq) {
-            Some(Piece::Paper)
-        } else if self.pieces[Piece::Scissors as usize][layer as usize].is_set(&sq) {
-            Some(Piece::Scissors)
-        } else if self.pieces[Piece::Wise as usize][layer as usize].is_set(&sq) {
-            Some(Piece::Wise)
-        } else {
-            None
+        let stack = Stack::from_code(self.mailbox[sq.0 as usize])?;
+        match layer {
+            Layer::Lower => Some(stack.bottom),
+            Layer::Upper => stack.top,
         }
     }
 
     #[must_use]
     pub fn get_side_on(&self, sq: Square) -> Option<Side> {
-        if self.sides[Side::White as usize].is_set(&sq) {
-            Some(Side::White)
-        } else if self.sides[Side::Black as usize].is_set(&sq) {
-            Some(Side::Black)
-        } else {
-            None
+        Stack::from_code(self.mailbox[sq.0 as usize]).map(|stack| stack.side)
+    }
+
+    // Same pieces and side to move, ignoring the move counters
+    #[must_use]
+    pub fn same_board(&self, other: &Self) -> bool {
+        self.sides == other.sides && self.pieces == other.pieces && self.turn == other.turn
+    }
+}
+
+// All zero like the derived Default this replaced, so unlike empty() the
+// fullmove counter starts at 0. Arrays as long as the mailbox have no Default.
+impl Default for Position {
+    fn default() -> Self {
+        Self {
+            sides: Default::default(),
+            pieces: Default::default(),
+            mailbox: [0; NUM_SQUARES],
+            turn: Side::default(),
+            halfmoves: 0,
+            fullmoves: 0,
         }
     }
 }
 
 impl fmt::Display for Position {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for y in (0..7).rev() {
+        for y in (0..SHAPE.rows).rev() {
             let is_long = y % 2 == 1;
 
             if !is_long {
                 write!(f, "  ")?;
             }
 
-            for x in 0..6 + is_long as i32 {
+            for x in 0..SHAPE.row_len(y) {
                 write!(f, "  ")?;
 
                 let sq = Square::from_coords(x, y);
-                let bottom = self.get_piece_on(Layer::Lower, sq);
-                let top = self.get_piece_on(Layer::Upper, sq);
-                let side = self.get_side_on(sq);
-
-                match (bottom, side) {
-                    (Some(Piece::Rock), Some(Side::White)) => write!(f, "R")?,
-                    (Some(Piece::Rock), Some(Side::Black)) => write!(f, "r")?,
-                    (Some(Piece::Paper), Some(Side::White)) => write!(f, "P")?,
-                    (Some(Piece::Paper), Some(Side::Black)) => write!(f, "p")?,
-                    (Some(Piece::Scissors), Some(Side::White)) => write!(f, "S")?,
-                    (Some(Piece::Scissors), Some(Side::Black)) => write!(f, "s")?,
-                    (Some(Piece::Wise), Some(Side::White)) => write!(f, "W")?,
-                    (Some(Piece::Wise), Some(Side::Black)) => write!(f, "w")?,
-                    (None, _) => write!(f, ".")?,
-                    (_, _) => write!(f, "?")?,
-                }
 
-                match (top, side) {
-                    (Some(Piece::Rock), Some(Side::White)) => write!(f, "R")?,
-                    (Some(Piece::Rock), Some(Side::Black)) => write!(f, "r")?,
-                    (Some(Piece::Paper), Some(Side::White)) => write!(f, "P")?,
-                    (Some(Piece::Paper), Some(Side::Black)) => write!(f, "p")?,
-                    (Some(Piece::Scissors), Some(Side::White)) => write!(f, "S")?,
-                    (Some(Piece::Scissors), Some(Side::Black)) => write!(f, "s")?,
-                    (Some(Piece::Wise), Some(Side::White)) => write!(f, "W")?,
-                    (Some(Piece::Wise), Some(Side::Black)) => write!(f, "w")?,
-                    (None, _) => write!(f, ".")?,
-                    (_, _) => write!(f, "?")?,
+                match self.stack_on(sq) {
+                    Some(stack) => {
+                        write!(f, "{}", piece_char(stack.side, stack.bottom))?;
+                        match stack.top {
+                            Some(top) => write!(f, "{}", piece_char(stack.side, top))?,
+                            None => write!(f, ".")?,
+                        }
+                    }
+                    None => write!(f, "..")?,
                 }
             }
 
@@ -0,0 +1,137 @@
+use crate::{mv::Mv, position::Position, result::GameResult};
+
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    // Uniform in 0..n, n must be non-zero
+    #[must_use]
+    fn below(&mut self, n: u32) -> u32 {
+        debug_assert!(n > 0);
+        (((self.next_u64() >> 32) * n as u64) >> 32) as u32
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[must_use]
+pub fn random_move(pos: &Position, rng: &mut impl Rng) -> Option<Mv> {
+    let moves = pos.legal_moves();
+
+    if moves.is_empty() {
+        None
+    } else {
+        Some(moves[rng.below(moves.len() as u32) as usize])
+    }
+}
+
+// Play random moves until the game ends or max_plies is reached
+// The result is None if the game didn't finish, or the side to move had no moves
+#[must_use]
+pub fn playout(pos: &Position, rng: &mut impl Rng, max_plies: i32) -> (Option<GameResult>, i32) {
+    let mut npos = *pos;
+    let mut plies = 0;
+
+    loop {
+        if let Some(result) = npos.result() {
+            return (Some(result), plies);
+        }
+
+        if plies >= max_plies {
+            return (None, plies);
+        }
+
+        match random_move(&npos, rng) {
+            Some(mv) => npos.makemove(&mv),
+            None => return (None, plies),
+        }
+
+        plies += 1;
+    }
+}
+
+// Play exactly ply random moves from pos
+// The result is None if the game ended before getting there
+#[must_use]
+pub fn random_position(pos: &Position, rng: &mut impl Rng, ply: i32) -> Option<Position> {
+    let mut npos = *pos;
+
+    for _ in 0..ply {
+        if npos.result().is_some() {
+            return None;
+        }
+
+        let mv = random_move(&npos, rng)?;
+        npos.makemove(&mv);
+    }
+
+    Some(npos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn below() {
+        let mut rng = SplitMix64::new(0);
+
+        for n in 1..100 {
+            assert!(rng.below(n) < n);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn reproducible() {
+        let pos = Position::from_fen("startpos");
+
+        for seed in 0..20 {
+            let a = playout(&pos, &mut SplitMix64::new(seed), 1000);
+            let b = playout(&pos, &mut SplitMix64::new(seed), 1000);
+            assert_eq!(a, b);
+            assert!(a.0.is_some());
+
+            let a = random_position(&pos, &mut SplitMix64::new(seed), 8).map(|p| p.get_fen());
+            let b = random_position(&pos, &mut SplitMix64::new(seed), 8).map(|p| p.get_fen());
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn max_plies() {
+        let pos = Position::from_fen("startpos");
+        let mut rng = SplitMix64::new(1);
+        assert_eq!(playout(&pos, &mut rng, 0), (None, 0));
+        assert_eq!(playout(&pos, &mut rng, 3), (None, 3));
+    }
+
+    #[test]
+    fn positions() {
+        let pos = Position::from_fen("startpos");
+        let mut rng = SplitMix64::new(2);
+
+        for ply in 0..10 {
+            if let Some(npos) = random_position(&pos, &mut rng, ply) {
+                assert_eq!(npos.halfmoves, ply);
+            }
+        }
+    }
+}
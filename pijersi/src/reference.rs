@@ -0,0 +1,364 @@
+// A deliberately plain move generator working square by square on an array
+// of stacks, with its own board geometry. It's only meant to check the
+// bitboard code against, so it favours being obviously right over being fast.
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{
+    geometry::{NUM_SQUARES, SHAPE},
+    position::{Piece, Position},
+    side::Side,
+    square::Square,
+    stack::Stack,
+};
+
+type Board = [Option<Stack>; NUM_SQUARES];
+
+// Rows alternate between short and long, starting with a short row. Only the
+// sizes are taken from the board shape, the layout is worked out here.
+fn row_len(y: i32) -> i32 {
+    SHAPE.short + y % 2
+}
+
+fn coords(sq: usize) -> (i32, i32) {
+    let mut idx = sq as i32;
+    let mut y = 0;
+    while idx >= row_len(y) {
+        idx -= row_len(y);
+        y += 1;
+    }
+    (idx, y)
+}
+
+fn index(x: i32, y: i32) -> Option<usize> {
+    if !(0..SHAPE.rows).contains(&y) || !(0..row_len(y)).contains(&x) {
+        return None;
+    }
+    Some(((0..y).map(row_len).sum::<i32>() + x) as usize)
+}
+
+// The six directions as east, west, then the four diagonals. A short row sits
+// half a square to the right of the long rows either side of it.
+fn neighbour(sq: usize, dir: usize) -> Option<usize> {
+    let (x, y) = coords(sq);
+    let shift = if y % 2 == 0 { 1 } else { 0 };
+    match dir {
+        0 => index(x + 1, y),
+        1 => index(x - 1, y),
+        2 => index(x + shift, y + 1),
+        3 => index(x + shift - 1, y + 1),
+        4 => index(x + shift, y - 1),
+        5 => index(x + shift - 1, y - 1),
+        _ => None,
+    }
+}
+
+fn is_adjacent(a: usize, b: usize) -> bool {
+    (0..6).any(|dir| neighbour(a, dir) == Some(b))
+}
+
+// Squares one or two steps away, the furthest any part of a move goes
+fn nearby(sq: usize) -> Vec<usize> {
+    let mut squares = Vec::new();
+    for dir1 in 0..6 {
+        if let Some(sq1) = neighbour(sq, dir1) {
+            squares.push(sq1);
+            squares.extend((0..6).filter_map(|dir2| neighbour(sq1, dir2)));
+        }
+    }
+    squares.sort();
+    squares.dedup();
+    squares
+}
+
+fn beats(attacker: Piece, target: Piece) -> bool {
+    matches!(
+        (attacker, target),
+        (Piece::Rock, Piece::Scissors)
+            | (Piece::Paper, Piece::Rock)
+            | (Piece::Scissors, Piece::Paper)
+    )
+}
+
+fn square_name(sq: usize) -> String {
+    let (x, y) = coords(sq);
+    format!("{}{}", (b'a' + y as u8) as char, x + 1)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RefPosition {
+    pub board: Board,
+    pub turn: Side,
+    pub halfmoves: i32,
+    pub fullmoves: i32,
+}
+
+impl RefPosition {
+    #[must_use]
+    pub fn from_position(pos: &Position) -> Self {
+        let mut board = [None; NUM_SQUARES];
+        for (sq, stack) in board.iter_mut().enumerate() {
+            *stack = pos.stack_on(Square(sq as u8));
+        }
+
+        Self {
+            board,
+            turn: pos.turn,
+            halfmoves: pos.halfmoves,
+            fullmoves: pos.fullmoves,
+        }
+    }
+
+    #[must_use]
+    pub fn fen(&self) -> String {
+        let mut rows = Vec::new();
+        for y in (0..SHAPE.rows).rev() {
+            let mut row = String::new();
+            let mut empty = 0;
+            for x in 0..row_len(y) {
+                match self.board[index(x, y).unwrap()] {
+                    Some(stack) => {
+                        if empty > 0 {
+                            row += &format!("{}", empty);
+                            empty = 0;
+                        }
+                        row += &format!("{}", stack);
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                row += &format!("{}", empty);
+            }
+            rows.push(row);
+        }
+
+        let turn = if self.turn == Side::White { "w" } else { "b" };
+        format!(
+            "{} {} {} {}",
+            rows.join("/"),
+            turn,
+            self.halfmoves,
+            self.fullmoves
+        )
+    }
+
+    fn ours(&self, sq: usize) -> Option<Stack> {
+        self.board[sq].filter(|stack| stack.side == self.turn)
+    }
+
+    // Can the piece finish on the square, either empty or captured
+    fn can_land(&self, piece: Piece, sq: usize) -> bool {
+        match self.board[sq] {
+            None => true,
+            Some(target) if target.side != self.turn => {
+                piece != Piece::Wise && beats(piece, target.visible())
+            }
+            Some(_) => false,
+        }
+    }
+
+    fn can_stack(&self, piece: Piece, sq: usize) -> bool {
+        match self.ours(sq) {
+            Some(target) if !target.is_tall() => {
+                piece != Piece::Wise || target.bottom == Piece::Wise
+            }
+            _ => false,
+        }
+    }
+
+    // The top piece, or a solo piece, moves one square. None if it can't.
+    fn move_piece(&self, fr: usize, to: usize) -> Option<Self> {
+        let stack = self.ours(fr)?;
+        let piece = stack.visible();
+        if !is_adjacent(fr, to) {
+            return None;
+        }
+
+        let mut next = *self;
+        next.board[fr] = stack.top.map(|_| Stack::solo(stack.side, stack.bottom));
+        if self.can_stack(piece, to) {
+            let mut target = self.board[to].unwrap();
+            target.top = Some(piece);
+            next.board[to] = Some(target);
+        } else if self.can_land(piece, to) {
+            next.board[to] = Some(Stack::solo(stack.side, piece));
+        } else {
+            return None;
+        }
+
+        Some(next)
+    }
+
+    // The whole stack moves one square, or two in a line over an empty square
+    fn move_stack(&self, fr: usize, to: usize) -> Option<Self> {
+        let stack = self.ours(fr)?;
+        let reachable = (0..6).any(|dir| match neighbour(fr, dir) {
+            Some(sq) if sq == to => true,
+            Some(middle) => self.board[middle].is_none() && neighbour(middle, dir) == Some(to),
+            None => false,
+        });
+        if !reachable || !self.can_land(stack.visible(), to) {
+            return None;
+        }
+
+        let mut next = *self;
+        next.board[fr] = None;
+        next.board[to] = Some(stack);
+        Some(next)
+    }
+
+    fn finish(&self) -> Self {
+        // makemove leaves the fullmove counter alone
+        Self {
+            turn: !self.turn,
+            halfmoves: self.halfmoves + 1,
+            ..*self
+        }
+    }
+
+    // Every legal move as its string and the position it leads to
+    #[must_use]
+    pub fn moves(&self) -> Vec<(String, Self)> {
+        let mut moves = Vec::new();
+        let mut add = |squares: &[usize], pos: Self| {
+            let name: String = squares.iter().map(|&sq| square_name(sq)).collect();
+            moves.push((name, pos.finish()));
+        };
+
+        for fr in 0..NUM_SQUARES {
+            let Some(stack) = self.ours(fr) else {
+                continue;
+            };
+
+            for mid in nearby(fr) {
+                if stack.is_tall() {
+                    // The stack moves, then maybe its top piece moves on
+                    if let Some(moved) = self.move_stack(fr, mid) {
+                        add(&[fr, mid, mid], moved);
+                        for to in nearby(mid) {
+                            if let Some(destacked) = moved.move_piece(mid, to) {
+                                add(&[fr, mid, to], destacked);
+                            }
+                        }
+                    }
+
+                    // The top piece moves, then maybe the stack it joined moves on
+                    if let Some(moved) = self.move_piece(fr, mid) {
+                        add(&[fr, fr, mid], moved);
+                        if moved.board[mid].unwrap().is_tall() {
+                            for to in nearby(mid) {
+                                if let Some(restacked) = moved.move_stack(mid, to) {
+                                    add(&[fr, mid, to], restacked);
+                                }
+                            }
+                        }
+                    }
+                } else if let Some(moved) = self.move_piece(fr, mid) {
+                    // A solo piece moves, and if it stacked the stack may move on
+                    add(&[fr, mid], moved);
+                    if moved.board[mid].unwrap().is_tall() {
+                        for to in nearby(mid) {
+                            if let Some(restacked) = moved.move_stack(mid, to) {
+                                add(&[fr, mid, to], restacked);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{Rng, SplitMix64};
+    use alloc::string::ToString;
+
+    // Positions are compared directly, FENs are only built once per node and
+    // to report a mismatch since building hundreds per node is slow
+    fn compare(pos: &Position) {
+        let reference = RefPosition::from_position(pos);
+        assert_eq!(reference.fen(), pos.get_fen());
+
+        let mut expected = reference.moves();
+        let mut found: Vec<(String, RefPosition)> = pos
+            .moves()
+            .map(|mv| {
+                (
+                    mv.to_string(),
+                    RefPosition::from_position(&pos.after_move(&mv)),
+                )
+            })
+            .collect();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+
+        if found != expected {
+            let fens = |moves: Vec<(String, RefPosition)>| -> Vec<(String, String)> {
+                moves
+                    .into_iter()
+                    .map(|(mv, npos)| (mv, npos.fen()))
+                    .collect()
+            };
+            assert_eq!(fens(found), fens(expected), "{}", pos.get_fen());
+        }
+    }
+
+    #[test]
+    fn geometry() {
+        for sq in 0..NUM_SQUARES {
+            assert_eq!(square_name(sq), Square(sq as u8).to_string());
+            let (x, y) = coords(sq);
+            assert_eq!(index(x, y), Some(sq));
+
+            let neighbours = (0..6).filter_map(|dir| neighbour(sq, dir));
+            assert!(neighbours.clone().all(|n| is_adjacent(n, sq)));
+            assert!(neighbours.clone().count() >= 3);
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn fens() {
+        let fens = [
+            "startpos",
+            "6/1wwr-4/2R-s-2/1RRp-P-1WW1/2S-3/7/6 b 3 9",
+            "6/7/6/7/6/7/6 w 0 1",
+        ];
+
+        for fen in fens {
+            let pos = Position::from_fen(fen);
+            assert_eq!(RefPosition::from_position(&pos).fen(), pos.get_fen());
+        }
+    }
+
+    #[test]
+    fn random_trees() {
+        let mut rng = SplitMix64::new(0);
+
+        // Debug builds are too slow for the full walk, cargo test --release runs it
+        let games = if cfg!(debug_assertions) { 250 } else { 5000 };
+
+        for game in 0..games {
+            let mut pos = if game % 2 == 0 {
+                Position::from_fen("startpos")
+            } else {
+                Position::shuffled_start(rng.next_u64())
+            };
+
+            // Walk one random line to the end, checking every position on it
+            loop {
+                compare(&pos);
+                if pos.is_gameover() {
+                    break;
+                }
+                let moves = pos.legal_moves();
+                pos.makemove(&moves[rng.below(moves.len() as u32) as usize]);
+            }
+        }
+    }
+}
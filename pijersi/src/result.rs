@@ -3,15 +3,30 @@ use crate::{
     position::Position,
 };
 
-#[derive(PartialEq, Debug)]
+// The game is drawn once this many halfmoves have been played
+pub const MAX_HALFMOVES: i32 = 20;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum GameResult {
     WhiteWin,
     BlackWin,
     Draw,
 }
 
+// How results are written in EPD records and game trees
+#[must_use]
+pub fn result_str(result: Option<GameResult>) -> &'static str {
+    match result {
+        Some(GameResult::WhiteWin) => "white",
+        Some(GameResult::BlackWin) => "black",
+        Some(GameResult::Draw) => "draw",
+        None => "none",
+    }
+}
+
 impl Position {
     #[must_use]
+    #[allow(clippy::if_same_then_else)]
     pub fn result(&self) -> Option<GameResult> {
         let white_rps = self.get_white() & self.get_rps();
         let black_rps = self.get_black() & self.get_rps();
@@ -33,7 +48,7 @@ impl Position {
             Some(GameResult::BlackWin)
         }
         // Halfmoves
-        else if self.halfmoves >= 20 {
+        else if self.halfmoves >= MAX_HALFMOVES {
             Some(GameResult::Draw)
         }
         // Edge cases
@@ -43,7 +58,7 @@ impl Position {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(any(feature = "small-board", feature = "large-board"))))]
 mod tests {
     use super::*;
 
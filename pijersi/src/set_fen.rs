@@ -1,183 +1,181 @@
+#[cfg(feature = "alloc")]
+use crate::square::Square;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+
+use core::fmt;
+
 use crate::{
-    bitboard::Bitboard,
-    layer::Layer,
-    position::{Piece, Position},
-    side::Side,
-    square::Square,
+    bitboard::Bitboard, geometry::SHAPE, layer::Layer, position::Position, side::Side,
+    stack::piece_from_char, validate::PositionError,
 };
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FenError {
+    WrongRowCount(usize),
+    // The row counting from white's side, and how many squares it covered
+    WrongRowLength(i32, i32),
+    BadToken(char),
+    BadSide,
+    BadHalfmoves,
+    BadFullmoves,
+    TrailingFields,
+    BadPosition(PositionError),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::WrongRowCount(n) => {
+                write!(f, "expected {} rows but got {}", SHAPE.rows, n)
+            }
+            FenError::WrongRowLength(y, n) => write!(
+                f,
+                "row {} covers {} squares instead of {}",
+                (b'a' + *y as u8) as char,
+                n,
+                SHAPE.row_len(*y)
+            ),
+            FenError::BadToken(c) => write!(f, "unexpected '{}' in the board", c),
+            FenError::BadSide => write!(f, "side to move isn't w or b"),
+            FenError::BadHalfmoves => write!(f, "invalid halfmove counter"),
+            FenError::BadFullmoves => write!(f, "invalid fullmove counter"),
+            FenError::TrailingFields => write!(f, "unexpected fields after the fullmove counter"),
+            FenError::BadPosition(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FenError {}
+
+impl From<PositionError> for FenError {
+    fn from(err: PositionError) -> Self {
+        FenError::BadPosition(err)
+    }
+}
+
 impl Position {
-    pub fn set_fen(&mut self, fen: &str) -> () {
+    // Anything that isn't a well formed FEN of a valid position is an error,
+    // the counters can be left off
+    pub fn parse_fen(fen: &str) -> Result<Position, FenError> {
+        let pos = Self::try_from_fen(fen)?;
+        pos.validate()?;
+        Ok(pos)
+    }
+
+    // Panics on a malformed FEN, but loads invalid positions
+    pub fn set_fen(&mut self, fen: &str) {
+        *self = Self::try_from_fen(fen).unwrap_or_else(|err| panic!("bad fen {}: {}", fen, err));
+        debug_assert!(self.is_valid());
+    }
+
+    // A well formed FEN, whether or not the position it describes is valid
+    pub fn try_from_fen(fen: &str) -> Result<Position, FenError> {
         if fen == "startpos" {
-            self.set_fen("s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/6/P-S-R-WWS-R-P-/R-P-S-R-P-S- w 0 1");
-        } else {
-            *self = Self {
-                sides: [Bitboard::empty(), Bitboard::empty()],
-                pieces: [
-                    // Rock
-                    [Bitboard::empty(), Bitboard::empty()],
-                    // Paper
-                    [Bitboard::empty(), Bitboard::empty()],
-                    // Scissors
-                    [Bitboard::empty(), Bitboard::empty()],
-                    // Wise
-                    [Bitboard::empty(), Bitboard::empty()],
-                ],
-                turn: Side::White,
-                halfmoves: 0,
-                fullmoves: 1,
-            };
-
-            let mut parts = fen.split(' ');
+            return Ok(Self::startpos());
+        }
+
+        let mut pos = Self::empty();
+        let mut parts = fen.split(' ');
+
+        // Board, from black's side down
+        let board = parts.next().unwrap_or_default();
+        let num_rows = board.split('/').count();
+        if num_rows != SHAPE.rows as usize {
+            return Err(FenError::WrongRowCount(num_rows));
+        }
+
+        for (row, y) in board.split('/').zip((0..SHAPE.rows).rev()) {
+            let len = SHAPE.row_len(y);
+            let mut chars = row.chars();
             let mut x = 0;
-            let mut y = 6;
-            let mut layer = Layer::Lower;
 
-            // Board
-            if let Some(part) = parts.next() {
-                for c in part.chars() {
-                    let sq = Square::from_coords(x, y);
-                    let bb = Bitboard::from_square(&sq);
+            while let Some(c) = chars.next() {
+                if let Some(n) = c.to_digit(10).filter(|&n| n > 0) {
+                    x += n as i32;
+                } else {
+                    // A piece and then the one on top of it or '-'
+                    let (side, bottom) = piece_from_char(c).ok_or(FenError::BadToken(c))?;
+                    let top = match chars.next() {
+                        Some('-') => None,
+                        Some(t) => match piece_from_char(t) {
+                            Some((top_side, top)) if top_side == side => Some(top),
+                            _ => return Err(FenError::BadToken(t)),
+                        },
+                        None => return Err(FenError::BadToken(c)),
+                    };
 
-                    match c {
-                        'R' => {
-                            self.sides[Side::White as usize] |= bb;
-                            self.pieces[Piece::Rock as usize][layer as usize] ^= bb;
-                            layer = !layer;
-                            x += (layer == Layer::Lower) as i32;
-                        }
-                        'P' => {
-                            self.sides[Side::White as usize] |= bb;
-                            self.pieces[Piece::Paper as usize][layer as usize] ^= bb;
-                            layer = !layer;
-                            x += (layer == Layer::Lower) as i32;
-                        }
-                        'S' => {
-                            self.sides[Side::White as usize] |= bb;
-                            self.pieces[Piece::Scissors as usize][layer as usize] ^= bb;
-                            layer = !layer;
-                            x += (layer == Layer::Lower) as i32;
-                        }
-                        'W' => {
-                            self.sides[Side::White as usize] |= bb;
-                            self.pieces[Piece::Wise as usize][layer as usize] ^= bb;
-                            layer = !layer;
-                            x += if layer == Layer::Lower { 1 } else { 0 };
-                        }
-                        'r' => {
-                            self.sides[Side::Black as usize] |= bb;
-                            self.pieces[Piece::Rock as usize][layer as usize] ^= bb;
-                            layer = !layer;
-                            x += (layer == Layer::Lower) as i32;
-                        }
-                        'p' => {
-                            self.sides[Side::Black as usize] |= bb;
-                            self.pieces[Piece::Paper as usize][layer as usize] ^= bb;
-                            layer = !layer;
-                            x += (layer == Layer::Lower) as i32;
-                        }
-                        's' => {
-                            self.sides[Side::Black as usize] |= bb;
-                            self.pieces[Piece::Scissors as usize][layer as usize] ^= bb;
-                            layer = !layer;
-                            x += (layer == Layer::Lower) as i32;
-                        }
-                        'w' => {
-                            self.sides[Side::Black as usize] |= bb;
-                            self.pieces[Piece::Wise as usize][layer as usize] ^= bb;
-                            layer = !layer;
-                            x += (layer == Layer::Lower) as i32;
-                        }
-                        '-' => {
-                            x += 1;
-                            layer = Layer::Lower;
-                        }
-                        '1'..='7' => {
-                            x += (c as u8 - b'0') as i32;
-                            layer = Layer::Lower;
-                        }
-                        '/' => {
-                            x = 0;
-                            y -= 1;
-                            layer = Layer::Lower;
-                        }
-                        _ => panic!("Unrecognised FEN board token {}", c),
+                    let sq = SHAPE
+                        .index(x, y)
+                        .ok_or(FenError::WrongRowLength(y, x + 1))?;
+                    let bb = Bitboard::from_square(&sq);
+                    pos.sides[side as usize] |= bb;
+                    pos.pieces[bottom as usize][Layer::Lower as usize] |= bb;
+                    if let Some(top) = top {
+                        pos.pieces[top as usize][Layer::Upper as usize] |= bb;
                     }
+                    x += 1;
                 }
-            }
 
-            // Side to move
-            match parts.next() {
-                Some("W" | "w") => self.turn = Side::White,
-                Some("B" | "b") => self.turn = Side::Black,
-                _ => panic!("Unrecognised FEN side token"),
+                if x > len {
+                    return Err(FenError::WrongRowLength(y, x));
+                }
             }
 
-            // Halfmoves
-            match parts.next() {
-                Some(n) => self.halfmoves = n.parse::<i32>().unwrap(),
-                _ => {}
+            if x != len {
+                return Err(FenError::WrongRowLength(y, x));
             }
+        }
 
-            // Fullmoves
-            match parts.next() {
-                Some(n) => self.fullmoves = n.parse::<i32>().unwrap(),
-                _ => {}
-            }
+        pos.refresh_mailbox();
+
+        // Side to move
+        pos.turn = match parts.next() {
+            Some("W" | "w") => Side::White,
+            Some("B" | "b") => Side::Black,
+            _ => return Err(FenError::BadSide),
+        };
+
+        // Halfmoves
+        if let Some(n) = parts.next() {
+            pos.halfmoves = n.parse().map_err(|_| FenError::BadHalfmoves)?;
         }
 
-        debug_assert!(self.is_valid());
+        // Fullmoves
+        if let Some(n) = parts.next() {
+            pos.fullmoves = n.parse().map_err(|_| FenError::BadFullmoves)?;
+        }
+
+        if parts.next().is_some() {
+            return Err(FenError::TrailingFields);
+        }
+
+        Ok(pos)
     }
 
+    #[cfg(feature = "alloc")]
     #[must_use]
     pub fn get_fen(&self) -> String {
         let mut fen = String::new();
 
         // Board
-        for y in (0..=6).rev() {
+        for y in (0..SHAPE.rows).rev() {
             let mut num_spaces: i32 = 0;
 
-            for x in 0..6 + y % 2 {
+            for x in 0..SHAPE.row_len(y) {
                 let sq = Square::from_coords(x, y);
-                let piece_lower = self.get_piece_on(Layer::Lower, sq);
-                let piece_upper = self.get_piece_on(Layer::Upper, sq);
-                let side = self.get_side_on(sq);
 
-                let is_empty = side.is_none();
-
-                if !is_empty && num_spaces > 0 {
-                    fen += &num_spaces.to_string();
-                    num_spaces = 0;
-                }
-
-                // Lower
-                match (piece_lower, side) {
-                    (Some(Piece::Rock), Some(Side::White)) => fen += "R",
-                    (Some(Piece::Paper), Some(Side::White)) => fen += "P",
-                    (Some(Piece::Scissors), Some(Side::White)) => fen += "S",
-                    (Some(Piece::Wise), Some(Side::White)) => fen += "W",
-                    (Some(Piece::Rock), Some(Side::Black)) => fen += "r",
-                    (Some(Piece::Paper), Some(Side::Black)) => fen += "p",
-                    (Some(Piece::Scissors), Some(Side::Black)) => fen += "s",
-                    (Some(Piece::Wise), Some(Side::Black)) => fen += "w",
-                    (None, None) => num_spaces += 1,
-                    (_, _) => {}
-                }
+                match self.stack_on(sq) {
+                    Some(stack) => {
+                        if num_spaces > 0 {
+                            fen += &num_spaces.to_string();
+                            num_spaces = 0;
+                        }
 
-                // Upper
-                match (piece_upper, side) {
-                    (Some(Piece::Rock), Some(Side::White)) => fen += "R",
-                    (Some(Piece::Paper), Some(Side::White)) => fen += "P",
-                    (Some(Piece::Scissors), Some(Side::White)) => fen += "S",
-                    (Some(Piece::Wise), Some(Side::White)) => fen += "W",
-                    (Some(Piece::Rock), Some(Side::Black)) => fen += "r",
-                    (Some(Piece::Paper), Some(Side::Black)) => fen += "p",
-                    (Some(Piece::Scissors), Some(Side::Black)) => fen += "s",
-                    (Some(Piece::Wise), Some(Side::Black)) => fen += "w",
-                    (None, Some(_)) => {
-                        fen += "-";
+                        fen += &stack.to_string();
                     }
-                    (_, _) => {}
+                    None => num_spaces += 1,
                 }
             }
 
@@ -208,7 +206,11 @@ impl Position {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(
+    test,
+    feature = "alloc",
+    not(any(feature = "small-board", feature = "large-board"))
+))]
 mod tests {
     use super::*;
 
@@ -261,6 +263,58 @@ mod tests {
             println!("{}", fen);
             let pos = Position::from_fen(fen);
             assert_eq!(fen, pos.get_fen());
+            assert_eq!(Position::parse_fen(fen), Ok(pos));
         }
     }
+
+    #[test]
+    fn errors() {
+        let tests = [
+            ("", FenError::WrongRowCount(1)),
+            ("6/7/6/7/6/7/6/6 w 0 1", FenError::WrongRowCount(8)),
+            ("6/7/6/7/6/7 w 0 1", FenError::WrongRowCount(6)),
+            ("7/7/6/7/6/7/6 w 0 1", FenError::WrongRowLength(6, 7)),
+            ("6/6/6/7/6/7/6 w 0 1", FenError::WrongRowLength(5, 6)),
+            ("6/7/6/7/6/7/8 w 0 1", FenError::WrongRowLength(0, 8)),
+            (
+                "6/7/6/7/6/7/R-R-R-R-R-R-R- w 0 1",
+                FenError::WrongRowLength(0, 7),
+            ),
+            ("6/7/6/7/6/7/33R- w 0 1", FenError::WrongRowLength(0, 7)),
+            ("6/7/6/7/6/7/0R-5 w 0 1", FenError::BadToken('0')),
+            ("6/7/6/7/6/7/X-5 w 0 1", FenError::BadToken('X')),
+            ("6/7/6/7/6/7/Rr5 w 0 1", FenError::BadToken('r')),
+            ("6/7/6/7/6/7/R5 w 0 1", FenError::BadToken('5')),
+            ("6/7/6/7/6/7/5R w 0 1", FenError::BadToken('R')),
+            ("6/7/6/7/6/7/-6 w 0 1", FenError::BadToken('-')),
+            ("6/7/6/7/6/7/6", FenError::BadSide),
+            ("6/7/6/7/6/7/6 x 0 1", FenError::BadSide),
+            ("6/7/6/7/6/7/6  0 1", FenError::BadSide),
+            ("6/7/6/7/6/7/6 w x 1", FenError::BadHalfmoves),
+            ("6/7/6/7/6/7/6 w 0 x", FenError::BadFullmoves),
+            ("6/7/6/7/6/7/6 w 0 1 x", FenError::TrailingFields),
+            (
+                "6/7/6/7/6/7/RW5 w 0 1",
+                FenError::BadPosition(PositionError::WiseOnRps(Square(0))),
+            ),
+            (
+                "6/7/6/7/6/7/6 w -1 1",
+                FenError::BadPosition(PositionError::BadHalfmoves(-1)),
+            ),
+        ];
+
+        for (fen, err) in tests {
+            assert_eq!(Position::parse_fen(fen), Err(err), "{}", fen);
+        }
+
+        // The counters are optional
+        assert_eq!(
+            Position::parse_fen("6/7/6/7/6/7/6 b").map(|pos| pos.get_fen()),
+            Ok("6/7/6/7/6/7/6 b 0 1".to_string())
+        );
+        assert_eq!(
+            Position::parse_fen("startpos"),
+            Ok(Position::from_fen("startpos"))
+        );
+    }
 }
@@ -0,0 +1,262 @@
+use crate::{
+    bitboard::Bitboard,
+    geometry::{NUM_SQUARES, SHAPE},
+    layer::Layer,
+    position::{Piece, Position},
+    random::{Rng, SplitMix64},
+    side::Side,
+    square::Square,
+};
+
+// White starts on its two home rows. The home row runs rock, paper, scissors
+// from the left and the row in front of it paper, scissors, rock, with the wise
+// stack in its middle. Black's setup is white's rotated 180 degrees.
+const WISE: u8 = (SHAPE.row_start(1) + SHAPE.row_len(1) / 2) as u8;
+const NUM_SLOTS: usize = SHAPE.row_start(2) as usize - 1;
+const PIECES: [Piece; 3] = [Piece::Rock, Piece::Paper, Piece::Scissors];
+
+// White's home squares in index order, skipping the wise stack, and the piece
+// each of them gets in the standard start
+const SLOTS: [u8; NUM_SLOTS] = {
+    let mut slots = [0; NUM_SLOTS];
+    let mut i = 0;
+    while i < NUM_SLOTS {
+        slots[i] = if i < WISE as usize { i } else { i + 1 } as u8;
+        i += 1;
+    }
+    slots
+};
+
+const LAYOUT: [Piece; NUM_SLOTS] = {
+    let mut layout = [Piece::Rock; NUM_SLOTS];
+    let mut i = 0;
+    while i < NUM_SLOTS {
+        let (x, y) = SHAPE.coords(Square(SLOTS[i]));
+        layout[i] = PIECES[(x + y) as usize % 3];
+        i += 1;
+    }
+    layout
+};
+
+// How many of each rps piece a side starts with
+const COUNTS: [u32; 3] = {
+    let mut counts = [0; 3];
+    let mut i = 0;
+    while i < NUM_SLOTS {
+        counts[LAYOUT[i] as usize] += 1;
+        i += 1;
+    }
+    counts
+};
+
+// 12! / (4! * 4! * 4!) on the standard board
+pub const NUM_START_POSITIONS: u32 = arrangements(&COUNTS);
+
+#[must_use]
+fn mirror(sq: u8) -> Square {
+    Square((NUM_SQUARES - 1) as u8 - sq)
+}
+
+#[must_use]
+const fn binomial(n: u32, k: u32) -> u64 {
+    let mut result = 1;
+    let mut i = 0;
+    while i < k {
+        result = result * (n - i) as u64 / (i + 1) as u64;
+        i += 1;
+    }
+    result
+}
+
+#[must_use]
+const fn arrangements(counts: &[u32; 3]) -> u32 {
+    let [a, b, c] = *counts;
+    (binomial(a + b + c, a) * binomial(b + c, b)) as u32
+}
+
+impl Position {
+    #[must_use]
+    pub fn shuffled_start(seed: u64) -> Self {
+        let mut rng = SplitMix64::new(seed);
+        Self::from_start_index(rng.below(NUM_START_POSITIONS)).unwrap()
+    }
+
+    #[must_use]
+    pub fn startpos() -> Self {
+        Self::from_layout(&LAYOUT)
+    }
+
+    // None past the last setup
+    #[must_use]
+    pub fn from_start_index(idx: u32) -> Option<Self> {
+        if idx >= NUM_START_POSITIONS {
+            return None;
+        }
+
+        let mut layout = LAYOUT;
+        let mut counts = COUNTS;
+        let mut idx = idx;
+
+        for slot in &mut layout {
+            for piece in PIECES {
+                if counts[piece as usize] == 0 {
+                    continue;
+                }
+
+                counts[piece as usize] -= 1;
+                let n = arrangements(&counts);
+
+                if idx < n {
+                    *slot = piece;
+                    break;
+                }
+
+                idx -= n;
+                counts[piece as usize] += 1;
+            }
+        }
+
+        Some(Self::from_layout(&layout))
+    }
+
+    fn from_layout(layout: &[Piece; NUM_SLOTS]) -> Self {
+        let mut pos = Self::empty();
+
+        for (sq, piece) in SLOTS.into_iter().zip(*layout) {
+            pos.add_start_piece(sq, piece, Layer::Lower);
+        }
+        pos.add_start_piece(WISE, Piece::Wise, Layer::Lower);
+        pos.add_start_piece(WISE, Piece::Wise, Layer::Upper);
+        pos.refresh_mailbox();
+
+        debug_assert!(pos.is_valid());
+
+        pos
+    }
+
+    // The index of this setup, or None if it isn't one of the shuffled starts
+    #[must_use]
+    pub fn start_index(&self) -> Option<u32> {
+        let mut counts = COUNTS;
+        let mut idx = 0;
+
+        for sq in SLOTS {
+            let piece = self.get_piece_on(Layer::Lower, Square(sq))?;
+
+            for other in PIECES {
+                if other == piece {
+                    break;
+                }
+
+                if counts[other as usize] > 0 {
+                    counts[other as usize] -= 1;
+                    idx += arrangements(&counts);
+                    counts[other as usize] += 1;
+                }
+            }
+
+            if piece == Piece::Wise || counts[piece as usize] == 0 {
+                return None;
+            }
+
+            counts[piece as usize] -= 1;
+        }
+
+        let start = Self::from_start_index(idx)?;
+        if self.sides == start.sides
+            && self.pieces == start.pieces
+            && self.turn == start.turn
+            && self.halfmoves == start.halfmoves
+            && self.fullmoves == start.fullmoves
+        {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+
+    fn add_start_piece(&mut self, sq: u8, piece: Piece, layer: Layer) {
+        let white = Bitboard::from_square(&Square(sq));
+        let black = Bitboard::from_square(&mirror(sq));
+
+        self.sides[Side::White as usize] |= white;
+        self.sides[Side::Black as usize] |= black;
+        self.pieces[piece as usize][layer as usize] |= white | black;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn startpos() {
+        assert_eq!(COUNTS, [4, 4, 4]);
+        assert_eq!(NUM_START_POSITIONS, 34_650);
+
+        let pos = Position::from_fen("startpos");
+        assert_eq!(pos.get_fen(), Position::startpos().get_fen());
+        let idx = pos.start_index().unwrap();
+        assert_eq!(
+            Position::from_start_index(idx).unwrap().get_fen(),
+            pos.get_fen()
+        );
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn not_starts() {
+        let tests = [
+            "6/7/6/7/6/7/6 w 0 1",
+            "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/6/P-S-R-WWS-R-P-/R-P-S-R-P-S- b 0 1",
+            "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/6/P-S-R-WWS-R-P-/R-P-S-R-P-S- w 1 1",
+            "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/6/P-S-R-WWS-R-P-/R-P-S-R-P-R- w 0 1",
+            "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/6/P-S-R-WWS-R-P-/P-R-S-R-P-S- w 0 1",
+        ];
+
+        for fen in tests {
+            println!("{}", fen);
+            assert_eq!(Position::from_fen(fen).start_index(), None);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn indices() {
+        let first = Position::from_start_index(0).unwrap();
+        let last = Position::from_start_index(NUM_START_POSITIONS - 1).unwrap();
+        assert!(Position::from_start_index(NUM_START_POSITIONS).is_none());
+        assert!(Position::from_start_index(u32::MAX).is_none());
+        assert_eq!(
+            first.get_fen(),
+            "p-p-r-r-r-r-/s-s-s-wws-p-p-/6/7/6/P-P-S-WWS-S-S-/R-R-R-R-P-P- w 0 1"
+        );
+        assert_eq!(
+            last.get_fen(),
+            "p-p-s-s-s-s-/r-r-r-wwr-p-p-/6/7/6/P-P-R-WWR-R-R-/S-S-S-S-P-P- w 0 1"
+        );
+
+        for idx in (0..NUM_START_POSITIONS).step_by(97) {
+            let pos = Position::from_start_index(idx).unwrap();
+            assert_eq!(pos.start_index(), Some(idx));
+            assert_eq!(Position::from_fen(&pos.get_fen()).start_index(), Some(idx));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn seeded() {
+        for seed in 0..10 {
+            let a = Position::shuffled_start(seed);
+            let b = Position::shuffled_start(seed);
+            assert_eq!(a.get_fen(), b.get_fen());
+            assert!(a.start_index().is_some());
+
+            let mut b = a;
+            b.turn = Side::Black;
+            assert_eq!(a.count_moves(), b.count_moves());
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt, ops::Not};
+use core::{fmt, ops::Not};
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 #[must_use]
@@ -11,7 +11,6 @@ pub enum Side {
 impl Not for Side {
     type Output = Self;
 
-    #[must_use]
     fn not(self) -> Self {
         match self {
             Side::White => Side::Black,
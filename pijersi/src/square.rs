@@ -1,43 +1,72 @@
-use std::fmt;
+use core::{fmt, str::FromStr};
+
+use crate::geometry::{NUM_SQUARES, SHAPE};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Square(pub u8);
 
+// (file, rank) of every square
+const COORDS: [(i32, i32); NUM_SQUARES] = {
+    let mut table = [(0, 0); NUM_SQUARES];
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        table[sq] = SHAPE.coords(Square(sq as u8));
+        sq += 1;
+    }
+    table
+};
+
 impl Square {
     #[must_use]
     pub fn from_coords(x: i32, y: i32) -> Self {
-        Self(6 * y as u8 + x as u8 + y as u8 / 2)
+        Self((SHAPE.row_start(y) + x) as u8)
     }
 
     #[must_use]
     pub fn file(&self) -> i32 {
-        let lut = [
-            0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 0, 1, 2,
-            3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 0, 1, 2, 3, 4, 5,
-        ];
-        lut[self.0 as usize]
+        COORDS[self.0 as usize].0
     }
 
     #[must_use]
     pub fn rank(&self) -> i32 {
-        let lut = [
-            0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 4, 4, 4,
-            4, 4, 4, 5, 5, 5, 5, 5, 5, 5, 6, 6, 6, 6, 6, 6,
-        ];
-        lut[self.0 as usize]
+        COORDS[self.0 as usize].1
     }
 }
 
 impl fmt::Display for Square {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let files = ['1', '2', '3', '4', '5', '6', '7'];
-        let ranks = ['a', 'b', 'c', 'd', 'e', 'f', 'g'];
-        let file_char = files[self.file() as usize];
-        let rank_char = ranks[self.rank() as usize];
+        let file_char = (b'1' + self.file() as u8) as char;
+        let rank_char = (b'a' + self.rank() as u8) as char;
         write!(f, "{}{}", rank_char, file_char)
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParseSquareError;
+
+impl fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid square")
+    }
+}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(rank @ 'a'..='z'), Some(file @ '1'..='9'), None) => {
+                let y = rank as i32 - 'a' as i32;
+                let x = file as i32 - '1' as i32;
+                SHAPE.index(x, y).ok_or(ParseSquareError)
+            }
+            _ => Err(ParseSquareError),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +103,7 @@ mod tests {
         assert_eq!(Square(6).rank(), 1);
     }
 
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
     #[test]
     fn string() {
         assert_eq!(format!("{}", Square(0)), "a1");
@@ -84,4 +114,17 @@ mod tests {
         assert_eq!(format!("{}", Square(43)), "g5");
         assert_eq!(format!("{}", Square(44)), "g6");
     }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn parse() {
+        for idx in 0..NUM_SQUARES as u8 {
+            let sq = Square(idx);
+            assert_eq!(sq.to_string().parse(), Ok(sq));
+        }
+
+        for str in ["", "a", "a0", "a7", "c7", "b8", "h1", "A1", "a1a", "1a"] {
+            assert_eq!(str.parse::<Square>(), Err(ParseSquareError));
+        }
+    }
 }
@@ -0,0 +1,301 @@
+use core::fmt;
+
+use crate::{
+    bitboard::Bitboard,
+    geometry::NUM_SQUARES,
+    layer::Layer,
+    position::{Piece, Position},
+    side::Side,
+    square::Square,
+};
+
+const PIECES: [Piece; 4] = [Piece::Rock, Piece::Paper, Piece::Scissors, Piece::Wise];
+
+// Mailbox code layout: 0 is empty, otherwise 0b1sttbb with the top stored as piece + 1
+const OCCUPIED: u8 = 0x40;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stack {
+    pub side: Side,
+    pub bottom: Piece,
+    pub top: Option<Piece>,
+}
+
+impl Stack {
+    #[must_use]
+    pub fn solo(side: Side, piece: Piece) -> Self {
+        Self {
+            side,
+            bottom: piece,
+            top: None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_tall(&self) -> bool {
+        self.top.is_some()
+    }
+
+    // The piece that moves when the stack moves as one, and that captures
+    #[must_use = "visible only reads the stack"]
+    pub fn visible(&self) -> Piece {
+        self.top.unwrap_or(self.bottom)
+    }
+
+    #[must_use]
+    pub fn to_code(&self) -> u8 {
+        let top = self.top.map_or(0, |piece| piece as u8 + 1);
+        OCCUPIED | (self.side as u8) << 5 | top << 2 | self.bottom as u8
+    }
+
+    #[must_use]
+    pub fn from_code(code: u8) -> Option<Self> {
+        if code & OCCUPIED == 0 {
+            return None;
+        }
+
+        let side = if code & 0x20 == 0 {
+            Side::White
+        } else {
+            Side::Black
+        };
+        let top = match (code >> 2) & 7 {
+            0 => None,
+            n => Some(PIECES[n as usize - 1]),
+        };
+
+        Some(Self {
+            side,
+            bottom: PIECES[(code & 3) as usize],
+            top,
+        })
+    }
+}
+
+#[must_use]
+pub fn piece_char(side: Side, piece: Piece) -> char {
+    let c = match piece {
+        Piece::Rock => 'r',
+        Piece::Paper => 'p',
+        Piece::Scissors => 's',
+        Piece::Wise => 'w',
+    };
+
+    match side {
+        Side::White => c.to_ascii_uppercase(),
+        Side::Black => c,
+    }
+}
+
+// The other way round from piece_char
+#[must_use]
+pub fn piece_from_char(c: char) -> Option<(Side, Piece)> {
+    let piece = match c.to_ascii_lowercase() {
+        'r' => Piece::Rock,
+        'p' => Piece::Paper,
+        's' => Piece::Scissors,
+        'w' => Piece::Wise,
+        _ => return None,
+    };
+
+    let side = if c.is_ascii_uppercase() {
+        Side::White
+    } else {
+        Side::Black
+    };
+    Some((side, piece))
+}
+
+impl fmt::Display for Stack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", piece_char(self.side, self.bottom))?;
+        match self.top {
+            Some(piece) => write!(f, "{}", piece_char(self.side, piece)),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+impl Position {
+    #[must_use]
+    pub fn stack_on(&self, sq: Square) -> Option<Stack> {
+        Stack::from_code(self.mailbox[sq.0 as usize])
+    }
+
+    pub fn clear_square(&mut self, sq: Square) {
+        self.mailbox[sq.0 as usize] = 0;
+
+        let bb = !Bitboard::from_square(&sq);
+
+        self.sides[Side::White as usize] &= bb;
+        self.sides[Side::Black as usize] &= bb;
+
+        for piece in &mut self.pieces {
+            piece[Layer::Lower as usize] &= bb;
+            piece[Layer::Upper as usize] &= bb;
+        }
+    }
+
+    pub fn set_stack(&mut self, sq: Square, stack: Stack) {
+        let bb = Bitboard::from_square(&sq);
+
+        self.clear_square(sq);
+
+        self.sides[stack.side as usize] |= bb;
+        self.pieces[stack.bottom as usize][Layer::Lower as usize] |= bb;
+        if let Some(top) = stack.top {
+            self.pieces[top as usize][Layer::Upper as usize] |= bb;
+        }
+        self.mailbox[sq.0 as usize] = stack.to_code();
+
+        debug_assert!(self.is_valid());
+    }
+
+    // Reads the stack from the bitboards alone, ignoring the mailbox
+    #[must_use]
+    pub(crate) fn stack_from_bitboards(&self, sq: Square) -> Option<Stack> {
+        let side = if self.sides[Side::White as usize].is_set(&sq) {
+            Side::White
+        } else if self.sides[Side::Black as usize].is_set(&sq) {
+            Side::Black
+        } else {
+            return None;
+        };
+
+        let find = |layer: Layer| {
+            PIECES
+                .into_iter()
+                .find(|&piece| self.pieces[piece as usize][layer as usize].is_set(&sq))
+        };
+
+        Some(Stack {
+            side,
+            bottom: find(Layer::Lower)?,
+            top: find(Layer::Upper),
+        })
+    }
+
+    // Rebuilds the mailbox after the bitboards were edited directly
+    pub fn refresh_mailbox(&mut self) {
+        for idx in 0..NUM_SQUARES {
+            self.mailbox[idx] = self
+                .stack_from_bitboards(Square(idx as u8))
+                .map_or(0, |stack| stack.to_code());
+        }
+    }
+
+    // First square where the mailbox disagrees with the bitboards
+    #[must_use]
+    pub(crate) fn mailbox_mismatch(&self) -> Option<Square> {
+        (0..NUM_SQUARES as u8).map(Square).find(|&sq| {
+            self.mailbox[sq.0 as usize] != self.stack_from_bitboards(sq).map_or(0, |s| s.to_code())
+        })
+    }
+
+    pub fn stacks(&self) -> impl Iterator<Item = (Square, Stack)> + '_ {
+        self.get_occupied()
+            .into_iter()
+            .filter_map(|sq| self.stack_on(sq).map(|stack| (sq, stack)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn startpos() {
+        let pos = Position::from_fen("startpos");
+
+        assert_eq!(
+            pos.stack_on(Square(0)),
+            Some(Stack::solo(Side::White, Piece::Rock))
+        );
+        assert_eq!(
+            pos.stack_on(Square(9)),
+            Some(Stack {
+                side: Side::White,
+                bottom: Piece::Wise,
+                top: Some(Piece::Wise),
+            })
+        );
+        assert_eq!(pos.stack_on(Square(22)), None);
+        assert_eq!(
+            pos.stack_on(Square(44)),
+            Some(Stack::solo(Side::Black, Piece::Rock))
+        );
+        assert_eq!(pos.stacks().count(), 26);
+    }
+
+    #[test]
+    fn strings() {
+        let tests = [
+            (Stack::solo(Side::White, Piece::Rock), "R-"),
+            (Stack::solo(Side::Black, Piece::Wise), "w-"),
+            (
+                Stack {
+                    side: Side::White,
+                    bottom: Piece::Paper,
+                    top: Some(Piece::Scissors),
+                },
+                "PS",
+            ),
+        ];
+
+        for (stack, str) in tests {
+            assert_eq!(stack.to_string(), str);
+        }
+    }
+
+    #[test]
+    fn codes() {
+        assert_eq!(Stack::from_code(0), None);
+
+        for side in [Side::White, Side::Black] {
+            for bottom in PIECES {
+                for top in [None].into_iter().chain(PIECES.map(Some)) {
+                    let stack = Stack { side, bottom, top };
+                    assert_ne!(stack.to_code(), 0);
+                    assert_eq!(Stack::from_code(stack.to_code()), Some(stack));
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn mutators() {
+        let mut pos = Position::from_fen("startpos");
+        let stack = Stack {
+            side: Side::Black,
+            bottom: Piece::Rock,
+            top: Some(Piece::Paper),
+        };
+
+        // Overwrite a tall stack with a different side
+        pos.set_stack(Square(9), stack);
+        assert!(pos.is_valid());
+        assert_eq!(pos.stack_on(Square(9)), Some(stack));
+
+        // Overwrite it again with a solo piece
+        pos.set_stack(Square(9), Stack::solo(Side::White, Piece::Scissors));
+        assert!(pos.is_valid());
+        assert_eq!(pos.get_piece_on(Layer::Upper, Square(9)), None);
+
+        pos.clear_square(Square(9));
+        assert_eq!(pos.stack_on(Square(9)), None);
+        assert_eq!(pos.validate(), Ok(()));
+        assert_eq!(
+            pos.get_fen(),
+            "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/6/P-S-R-1S-R-P-/R-P-S-R-P-S- w 0 1"
+        );
+
+        let mut rebuilt = Position::empty();
+        for (sq, stack) in pos.stacks() {
+            rebuilt.set_stack(sq, stack);
+        }
+        assert_eq!(rebuilt.get_fen(), pos.get_fen());
+    }
+}
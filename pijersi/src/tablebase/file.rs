@@ -0,0 +1,349 @@
+use std::{
+    fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{signature::Signature, Outcome, Table, TableIndex};
+use crate::hash::fnv1a;
+
+// File layout, all integers little endian:
+//   magic        4 bytes "PJTB"
+//   version      u16
+//   signature    8 bytes, piece counts for white then black in R, P, S, W order
+//   num_indices  u64
+//   wdl_len      u64, wdl_checksum u64
+//   dtw_len      u64, dtw_checksum u64
+//   wdl          run length encoded, one byte per index: 1 loss, 2 draw, 3 win
+//   dtw          run length encoded, one byte per index: plies to the end, 0 for draws
+// Runs are a value byte followed by the run length as an LEB128 varint.
+// Checksums are 64 bit FNV-1a over the encoded bytes.
+const MAGIC: [u8; 4] = *b"PJTB";
+pub const VERSION: u16 = 2;
+pub(crate) const HEADER_LEN: u64 = 4 + 2 + 8 + 8 + 4 * 8;
+pub const EXTENSION: &str = "pjtb";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TablebaseError {
+    Io(io::ErrorKind),
+    BadMagic,
+    BadVersion(u16),
+    BadChecksum(Signature),
+    Corrupt(Signature),
+    SignatureMismatch(Signature, Signature),
+    Missing(Signature),
+}
+
+impl fmt::Display for TablebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TablebaseError::Io(kind) => write!(f, "io error: {}", kind),
+            TablebaseError::BadMagic => write!(f, "not a tablebase file"),
+            TablebaseError::BadVersion(n) => write!(f, "unsupported tablebase version {}", n),
+            TablebaseError::BadChecksum(sig) => write!(f, "checksum mismatch in {}", sig),
+            TablebaseError::Corrupt(sig) => write!(f, "corrupt table {}", sig),
+            TablebaseError::SignatureMismatch(expected, found) => {
+                write!(f, "expected table {} but found {}", expected, found)
+            }
+            TablebaseError::Missing(sig) => write!(f, "no table for {}", sig),
+        }
+    }
+}
+
+impl std::error::Error for TablebaseError {}
+
+impl From<io::Error> for TablebaseError {
+    fn from(err: io::Error) -> Self {
+        TablebaseError::Io(err.kind())
+    }
+}
+
+fn encode(values: impl Iterator<Item = u8>) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut values = values.peekable();
+
+    while let Some(value) = values.next() {
+        let mut run = 1u64;
+        while values.next_if_eq(&value).is_some() {
+            run += 1;
+        }
+
+        bytes.push(value);
+        while run >= 0x80 {
+            bytes.push(run as u8 | 0x80);
+            run >>= 7;
+        }
+        bytes.push(run as u8);
+    }
+
+    bytes
+}
+
+// None if the runs are malformed or don't add up to len values. The length
+// comes from a header that's been checked against its signature, so it's no
+// bigger than the largest table.
+fn decode(bytes: &[u8], len: usize) -> Option<Vec<u8>> {
+    let mut values = Vec::with_capacity(len);
+    let mut bytes = bytes.iter();
+
+    while let Some(&value) = bytes.next() {
+        let mut run = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = *bytes.next()?;
+            if shift > 56 {
+                return None;
+            }
+            run |= ((b & 0x7f) as u64) << shift;
+            shift += 7;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+
+        if run == 0 || values.len() as u64 + run > len as u64 {
+            return None;
+        }
+        values.resize(values.len() + run as usize, value);
+    }
+
+    (values.len() == len).then_some(values)
+}
+
+fn wdl_code(outcome: Outcome) -> u8 {
+    match outcome {
+        Outcome::Loss(_) => 1,
+        Outcome::Draw => 2,
+        Outcome::Win(_) => 3,
+    }
+}
+
+// None for codes that don't exist
+pub(crate) fn outcome_from_codes(wdl: u8, dtw: u8) -> Option<Outcome> {
+    match wdl {
+        1 => Some(Outcome::Loss(dtw)),
+        2 => Some(Outcome::Draw),
+        3 => Some(Outcome::Win(dtw)),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct Header {
+    pub signature: Signature,
+    pub num_indices: u64,
+    pub wdl_len: u64,
+    pub wdl_checksum: u64,
+    pub dtw_len: u64,
+    pub dtw_checksum: u64,
+}
+
+impl Header {
+    pub fn read(r: &mut impl Read) -> Result<Self, TablebaseError> {
+        let mut buf = [0; HEADER_LEN as usize];
+        r.read_exact(&mut buf)?;
+
+        if buf[0..4] != MAGIC {
+            return Err(TablebaseError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([buf[4], buf[5]]);
+        if version != VERSION {
+            return Err(TablebaseError::BadVersion(version));
+        }
+
+        let mut signature = Signature::default();
+        for (i, &n) in buf[6..14].iter().enumerate() {
+            signature.counts[i / 4][i % 4] = n;
+        }
+        if !signature.is_possible() {
+            return Err(TablebaseError::Corrupt(signature));
+        }
+
+        let word = |i: usize| u64::from_le_bytes(buf[14 + 8 * i..22 + 8 * i].try_into().unwrap());
+        let header = Header {
+            signature,
+            num_indices: word(0),
+            wdl_len: word(1),
+            wdl_checksum: word(2),
+            dtw_len: word(3),
+            dtw_checksum: word(4),
+        };
+
+        // Also keeps the piece count down to tables that fit in memory
+        if signature.num_indices().map(|n| n as u64) != Some(header.num_indices) {
+            return Err(TablebaseError::Corrupt(signature));
+        }
+
+        Ok(header)
+    }
+
+    fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(&MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        for counts in self.signature.counts {
+            w.write_all(&counts)?;
+        }
+        for word in [
+            self.num_indices,
+            self.wdl_len,
+            self.wdl_checksum,
+            self.dtw_len,
+            self.dtw_checksum,
+        ] {
+            w.write_all(&word.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+// Reads one of the two value streams, checking its length and checksum
+pub(crate) fn read_stream(
+    r: &mut impl Read,
+    header: &Header,
+    len: u64,
+    expected: u64,
+) -> Result<Vec<u8>, TablebaseError> {
+    // Don't trust the length enough to allocate it up front
+    let mut bytes = vec![];
+    r.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(TablebaseError::Io(io::ErrorKind::UnexpectedEof));
+    }
+
+    if fnv1a(&bytes) != expected {
+        return Err(TablebaseError::BadChecksum(header.signature));
+    }
+
+    decode(&bytes, header.num_indices as usize).ok_or(TablebaseError::Corrupt(header.signature))
+}
+
+impl Signature {
+    // eg. "RPvS.pjtb"
+    #[must_use]
+    pub fn file_name(&self) -> String {
+        format!("{}.{}", self.to_string().replace(" vs ", "v"), EXTENSION)
+    }
+}
+
+impl Table {
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        let wdl = encode(self.outcomes.iter().map(|&outcome| wdl_code(outcome)));
+        let dtw = encode(
+            self.outcomes
+                .iter()
+                .map(|outcome| outcome.distance().unwrap_or(0)),
+        );
+
+        let header = Header {
+            signature: self.signature(),
+            num_indices: self.outcomes.len() as u64,
+            wdl_len: wdl.len() as u64,
+            wdl_checksum: fnv1a(&wdl),
+            dtw_len: dtw.len() as u64,
+            dtw_checksum: fnv1a(&dtw),
+        };
+
+        header.write(w)?;
+        w.write_all(&wdl)?;
+        w.write_all(&dtw)
+    }
+
+    pub fn read(r: &mut impl Read) -> Result<Self, TablebaseError> {
+        let header = Header::read(r)?;
+        let wdl = read_stream(r, &header, header.wdl_len, header.wdl_checksum)?;
+        let dtw = read_stream(r, &header, header.dtw_len, header.dtw_checksum)?;
+
+        let outcomes = wdl
+            .into_iter()
+            .zip(dtw)
+            .map(|(wdl, dtw)| outcome_from_codes(wdl, dtw))
+            .collect::<Option<Vec<Outcome>>>()
+            .ok_or(TablebaseError::Corrupt(header.signature))?;
+
+        Ok(Table {
+            index: TableIndex::new(header.signature),
+            outcomes,
+        })
+    }
+
+    // Writes the table into the directory under its signature's file name
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<PathBuf, TablebaseError> {
+        let path = dir.as_ref().join(self.signature().file_name());
+        let mut file = io::BufWriter::new(fs::File::create(&path)?);
+        self.write(&mut file)?;
+        file.flush()?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tablebase::Generator;
+
+    #[test]
+    fn run_lengths() {
+        let tests: [&[u8]; 5] = [&[], &[7], &[1, 1, 1, 2, 2, 1], &[0; 127], &[3; 100_000]];
+
+        for values in tests {
+            let bytes = encode(values.iter().copied());
+            assert_eq!(decode(&bytes, values.len()).as_deref(), Some(values));
+            assert_eq!(decode(&bytes, values.len() + 1), None);
+        }
+
+        assert_eq!(encode([5; 128].into_iter()), [5, 0x80, 0x01]);
+        assert_eq!(decode(&[5], 1), None);
+        assert_eq!(decode(&[5, 0], 0), None);
+    }
+
+    #[test]
+    fn file_names() {
+        let tests = [("R vs S", "RvS.pjtb"), ("RRPSW vs RPS", "RRPSWvRPS.pjtb")];
+
+        for (signature, name) in tests {
+            assert_eq!(signature.parse::<Signature>().unwrap().file_name(), name);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut generator = Generator::new();
+        let table = generator.generate("R vs S".parse().unwrap());
+
+        let mut bytes = vec![];
+        table.write(&mut bytes).unwrap();
+        // Both streams together still come out smaller than one of them raw
+        assert!(bytes.len() < table.outcomes().len());
+
+        let read = Table::read(&mut bytes.as_slice()).unwrap();
+        assert_eq!(read.signature(), table.signature());
+        assert_eq!(read.outcomes(), table.outcomes());
+
+        // Any damage has to be noticed
+        let sig = table.signature();
+        let tests = [
+            (0, b'X', TablebaseError::BadMagic),
+            (4, 9, TablebaseError::BadVersion(9)),
+            (6, 2, TablebaseError::Corrupt("RR vs S".parse().unwrap())),
+            (7, 4, TablebaseError::Corrupt("RPPPP vs S".parse().unwrap())),
+            (
+                HEADER_LEN as usize + 1,
+                0xff,
+                TablebaseError::BadChecksum(sig),
+            ),
+            (bytes.len() - 1, 0xff, TablebaseError::BadChecksum(sig)),
+        ];
+
+        for (offset, byte, error) in tests {
+            let mut damaged = bytes.clone();
+            damaged[offset] = byte;
+            assert_eq!(Table::read(&mut damaged.as_slice()).unwrap_err(), error);
+        }
+
+        assert_eq!(
+            Table::read(&mut &bytes[..bytes.len() - 1]).unwrap_err(),
+            TablebaseError::Io(io::ErrorKind::UnexpectedEof)
+        );
+    }
+}
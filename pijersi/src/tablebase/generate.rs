@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::{position::Position, result::GameResult, side::Side};
+
+use super::{signature::Signature, Outcome, Table, TableIndex, MAX_PIECES};
+
+// Solves tables by retrograde analysis, generating every smaller table the
+// captures can lead into first. Positions are solved with the halfmove counter
+// at zero and ignored, see Outcome::with_halfmoves for how it's applied after.
+// A side to move without legal moves is scored as a draw.
+#[derive(Default)]
+pub struct Generator {
+    tables: HashMap<Signature, Table>,
+}
+
+fn terminal(pos: &Position) -> Option<Outcome> {
+    let winner = match pos.result()? {
+        GameResult::WhiteWin => Side::White,
+        GameResult::BlackWin => Side::Black,
+        GameResult::Draw => return Some(Outcome::Draw),
+    };
+
+    if winner == pos.turn {
+        Some(Outcome::Win(0))
+    } else {
+        Some(Outcome::Loss(0))
+    }
+}
+
+impl Generator {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn table(&self, signature: &Signature) -> Option<&Table> {
+        self.tables.get(signature)
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = &Table> {
+        self.tables.values()
+    }
+
+    // The signature can't have more than MAX_PIECES pieces
+    pub fn generate(&mut self, signature: Signature) -> &Table {
+        assert!(signature.num_pieces() <= MAX_PIECES);
+
+        if !self.tables.contains_key(&signature) {
+            for sub in signature.subsets() {
+                self.generate(sub);
+            }
+
+            let table = self.solve(signature);
+            self.tables.insert(signature, table);
+        }
+
+        &self.tables[&signature]
+    }
+
+    // Looks the child up in the table being solved or in an earlier one,
+    // None if it's still unresolved
+    fn child_outcome(
+        &self,
+        index: &TableIndex,
+        outcomes: &[Option<Outcome>],
+        pos: &Position,
+    ) -> Option<Outcome> {
+        if let Some(outcome) = terminal(pos) {
+            return Some(outcome);
+        }
+
+        let child_signature = Signature::of(pos);
+        if child_signature == index.signature() {
+            outcomes[index.index(pos)]
+        } else {
+            let table = &self.tables[&child_signature];
+            Some(table.outcomes[table.index.index(pos)])
+        }
+    }
+
+    fn solve(&self, signature: Signature) -> Table {
+        let index = TableIndex::new(signature);
+        let mut outcomes = vec![None; index.len()];
+        let mut pending = vec![];
+
+        for (idx, outcome) in outcomes.iter_mut().enumerate() {
+            let pos = index.position(idx).unwrap();
+
+            if let Some(result) = terminal(&pos) {
+                *outcome = Some(result);
+            } else if pos.legal_moves().is_empty() {
+                *outcome = Some(Outcome::Draw);
+            } else {
+                pending.push(idx);
+            }
+        }
+
+        // Results from smaller tables can arrive late, so keep going until
+        // the longest of them has had a chance to propagate
+        let longest = self
+            .tables
+            .values()
+            .flat_map(|table| table.outcomes.iter())
+            .filter_map(|outcome| outcome.distance())
+            .max()
+            .unwrap_or(0);
+
+        let mut ply: u8 = 1;
+        loop {
+            let mut changed = false;
+            let mut unresolved = vec![];
+
+            for idx in pending {
+                let pos = index.position(idx).unwrap();
+                let mut all_won = true;
+                let mut longest_win = 0;
+                let mut outcome = None;
+
+                for mv in pos.legal_moves() {
+                    let mut child = pos.after_move(&mv);
+                    child.halfmoves = 0;
+
+                    match self.child_outcome(&index, &outcomes, &child) {
+                        Some(Outcome::Loss(n)) if n + 1 == ply => {
+                            outcome = Some(Outcome::Win(ply));
+                            break;
+                        }
+                        Some(Outcome::Win(n)) => longest_win = longest_win.max(n),
+                        _ => all_won = false,
+                    }
+                }
+
+                if outcome.is_none() && all_won && longest_win + 1 == ply {
+                    outcome = Some(Outcome::Loss(ply));
+                }
+
+                match outcome {
+                    Some(_) => {
+                        outcomes[idx] = outcome;
+                        changed = true;
+                    }
+                    None => unresolved.push(idx),
+                }
+            }
+
+            pending = unresolved;
+
+            if pending.is_empty() || (!changed && ply > longest) {
+                break;
+            }
+
+            ply = ply.checked_add(1).expect("distance doesn't fit in a u8");
+        }
+
+        Table {
+            index,
+            outcomes: outcomes
+                .into_iter()
+                .map(|outcome| outcome.unwrap_or(Outcome::Draw))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every outcome has to agree with the outcomes of its children
+    fn check(generator: &Generator, table: &Table) {
+        for (idx, &outcome) in table.outcomes().iter().enumerate() {
+            let pos = table.index().position(idx).unwrap();
+
+            if let Some(result) = terminal(&pos) {
+                assert_eq!(outcome, result);
+                continue;
+            }
+
+            let children: Vec<Outcome> = pos
+                .legal_moves()
+                .iter()
+                .map(|mv| {
+                    let mut child = pos.after_move(mv);
+                    child.halfmoves = 0;
+                    terminal(&child).unwrap_or_else(|| {
+                        let table = generator.table(&Signature::of(&child)).unwrap();
+                        table.outcomes()[table.index().index(&child)]
+                    })
+                })
+                .collect();
+
+            let fastest_win = children
+                .iter()
+                .filter_map(|child| match child {
+                    Outcome::Loss(n) => Some(n + 1),
+                    _ => None,
+                })
+                .min();
+            let all_won = children
+                .iter()
+                .all(|child| matches!(child, Outcome::Win(_)));
+            let slowest_loss = children.iter().filter_map(|child| child.distance()).max();
+
+            let expected = match (fastest_win, all_won && !children.is_empty()) {
+                (Some(n), _) => Outcome::Win(n),
+                (None, true) => Outcome::Loss(slowest_loss.unwrap() + 1),
+                (None, false) => Outcome::Draw,
+            };
+
+            assert_eq!(outcome, expected, "{}", pos.get_fen());
+        }
+    }
+
+    #[test]
+    fn consistent() {
+        let mut generator = Generator::new();
+
+        for str in ["R vs S", "P vs W", "RP vs S"] {
+            let signature = str.parse::<Signature>().unwrap();
+            generator.generate(signature);
+        }
+
+        for table in generator.tables() {
+            check(&generator, table);
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn probes() {
+        use crate::tablebase::Wdl;
+        let mut generator = Generator::new();
+        let table = generator.generate("R vs S".parse().unwrap());
+
+        let tests = [
+            // One step from the goal
+            ("6/R-6/6/3s-3/6/7/6 w 0 1", Wdl::Win, Some(1)),
+            ("6/R-6/6/3s-3/6/7/6 w 19 1", Wdl::Win, Some(1)),
+            ("6/R-6/6/3s-3/6/7/6 w 20 1", Wdl::Draw, None),
+            // Black walks home
+            ("6/7/6/7/6/s-6/5R- b 0 1", Wdl::Win, Some(1)),
+            // Already over
+            ("R-5/7/6/3s-3/6/7/6 b 0 1", Wdl::Loss, Some(0)),
+        ];
+
+        for (fen, wdl, distance) in tests {
+            let outcome = table.probe(&Position::from_fen(fen)).unwrap();
+            assert_eq!(outcome.wdl(), wdl, "{}", fen);
+            assert_eq!(outcome.distance(), distance, "{}", fen);
+        }
+
+        assert_eq!(table.probe(&Position::from_fen("startpos")), None);
+    }
+}
@@ -0,0 +1,424 @@
+use crate::{
+    geometry::{NUM_SQUARES, SHAPE},
+    position::{Piece, Position},
+    side::Side,
+    square::Square,
+    stack::Stack,
+};
+
+use super::{signature::Signature, MAX_PIECES};
+
+// Positions are grouped by layout, the stacks the signature's pieces make up.
+// Within a layout each group of identical stacks takes a set of the squares
+// the groups before it left free, ranked in the combinatorial number system,
+// so identical stacks aren't counted in every order and nothing can float or
+// share a square. The board is the same mirrored left to right, so when a
+// layout has a lone stack the first one is kept on the left half or the middle
+// column. The side to move is on top.
+
+const PIECES: [Piece; 4] = [Piece::Rock, Piece::Paper, Piece::Scissors, Piece::Wise];
+
+const BINOMIAL: [[usize; MAX_PIECES + 1]; NUM_SQUARES + 1] = {
+    let mut table = [[0; MAX_PIECES + 1]; NUM_SQUARES + 1];
+    let mut n = 0;
+    while n <= NUM_SQUARES {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= MAX_PIECES && n > 0 {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+};
+
+// The squares on the left half or in the middle column, in order
+const LEFT: ([Square; NUM_SQUARES], usize) = {
+    let mut squares = [Square(0); NUM_SQUARES];
+    let mut len = 0;
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        let (x, y) = SHAPE.coords(Square(sq as u8));
+        if 2 * x < SHAPE.row_len(y) {
+            squares[len] = Square(sq as u8);
+            len += 1;
+        }
+        sq += 1;
+    }
+    (squares, len)
+};
+
+// Where each square comes in LEFT, None for the right half
+const LEFT_RANKS: [Option<usize>; NUM_SQUARES] = {
+    let mut table = [None; NUM_SQUARES];
+    let mut i = 0;
+    while i < LEFT.1 {
+        table[LEFT.0[i].0 as usize] = Some(i);
+        i += 1;
+    }
+    table
+};
+
+// The square's place among the ones not yet used
+fn free_rank(sq: Square, used: u64) -> usize {
+    sq.0 as usize - (used & ((1 << sq.0) - 1)).count_ones() as usize
+}
+
+fn nth_free(used: u64, n: usize) -> Square {
+    (0..NUM_SQUARES as u8)
+        .map(Square)
+        .filter(|sq| used >> sq.0 & 1 == 0)
+        .nth(n)
+        .unwrap()
+}
+
+// Every way of stacking up the pieces, as sorted stack codes
+fn stackings(side: Side, counts: [u8; 4], stacks: &mut Vec<u8>, found: &mut Vec<Vec<u8>>) {
+    let Some(piece) = PIECES.into_iter().find(|&piece| counts[piece as usize] > 0) else {
+        let mut stacks = stacks.clone();
+        stacks.sort_unstable();
+        found.push(stacks);
+        return;
+    };
+
+    let mut counts = counts;
+    counts[piece as usize] -= 1;
+
+    stacks.push(Stack::solo(side, piece).to_code());
+    stackings(side, counts, stacks, found);
+    stacks.pop();
+
+    for other in PIECES {
+        if counts[other as usize] == 0 {
+            continue;
+        }
+
+        let mut rest = counts;
+        rest[other as usize] -= 1;
+
+        for (bottom, top) in [(piece, other), (other, piece)] {
+            if top == Piece::Wise && bottom != Piece::Wise {
+                continue;
+            }
+            let stack = Stack {
+                side,
+                bottom,
+                top: Some(top),
+            };
+            stacks.push(stack.to_code());
+            stackings(side, rest, stacks, found);
+            stacks.pop();
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct Layout {
+    // Every stack's code, sorted
+    codes: Vec<u8>,
+    // Identical stacks with how many of them there are, lone ones first
+    groups: Vec<(u8, usize)>,
+    // Placements of each group given the ones before it
+    sizes: Vec<usize>,
+    offset: usize,
+    len: usize,
+}
+
+impl Layout {
+    fn new(codes: Vec<u8>, offset: usize) -> Self {
+        let mut groups: Vec<(u8, usize)> = vec![];
+        for &code in &codes {
+            match groups.last_mut() {
+                Some((last, n)) if *last == code => *n += 1,
+                _ => groups.push((code, 1)),
+            }
+        }
+        groups.sort_unstable_by_key(|&(code, n)| (n, code));
+
+        let mut free = NUM_SQUARES;
+        let sizes: Vec<usize> = groups
+            .iter()
+            .enumerate()
+            .map(|(i, &(_, n))| {
+                let size = if i == 0 && n == 1 {
+                    LEFT.1
+                } else {
+                    BINOMIAL[free][n]
+                };
+                free -= n;
+                size
+            })
+            .collect();
+
+        Self {
+            codes,
+            groups,
+            len: sizes.iter().product(),
+            sizes,
+            offset,
+        }
+    }
+
+    fn is_mirrored(&self) -> bool {
+        matches!(self.groups.first(), Some(&(_, 1)))
+    }
+}
+
+// Maps the positions of one signature to indices and back
+#[derive(Clone, PartialEq, Debug)]
+pub struct TableIndex {
+    signature: Signature,
+    layouts: Vec<Layout>,
+    half: usize,
+}
+
+impl TableIndex {
+    #[must_use]
+    pub fn new(signature: Signature) -> Self {
+        assert!(signature.num_pieces() <= MAX_PIECES);
+
+        let mut sides = [vec![], vec![]];
+        for side in [Side::White, Side::Black] {
+            let found = &mut sides[side as usize];
+            stackings(side, signature.counts[side as usize], &mut vec![], found);
+            found.sort_unstable();
+            found.dedup();
+        }
+
+        let mut layouts: Vec<Layout> = vec![];
+        let mut offset = 0;
+        for white in &sides[0] {
+            for black in &sides[1] {
+                let mut codes = [white.as_slice(), black.as_slice()].concat();
+                codes.sort_unstable();
+                let layout = Layout::new(codes, offset);
+                offset += layout.len;
+                layouts.push(layout);
+            }
+        }
+
+        Self {
+            signature,
+            layouts,
+            half: offset,
+        }
+    }
+
+    #[must_use]
+    pub fn signature(&self) -> Signature {
+        self.signature
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        2 * self.half
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The position must have exactly this signature
+    #[must_use]
+    pub fn index(&self, pos: &Position) -> usize {
+        debug_assert_eq!(Signature::of(pos), self.signature);
+
+        let mut stacks = [(0, Square(0)); MAX_PIECES];
+        let mut codes = [0; MAX_PIECES];
+        let mut num_stacks = 0;
+        for sq in pos.get_occupied() {
+            let code = pos.stack_on(sq).unwrap().to_code();
+            stacks[num_stacks] = (code, sq);
+            codes[num_stacks] = code;
+            num_stacks += 1;
+        }
+        let stacks = &mut stacks[..num_stacks];
+        let codes = &mut codes[..num_stacks];
+        codes.sort_unstable();
+
+        let layout = self
+            .layouts
+            .iter()
+            .find(|layout| layout.codes == codes)
+            .unwrap();
+
+        if layout.is_mirrored() {
+            let (code, _) = layout.groups[0];
+            let &(_, sq) = stacks.iter().find(|(c, _)| *c == code).unwrap();
+            if LEFT_RANKS[sq.0 as usize].is_none() {
+                for (_, sq) in stacks.iter_mut() {
+                    *sq = sq.mirrored();
+                }
+            }
+        }
+
+        let mut idx = 0;
+        let mut used = 0u64;
+        for (i, (&(code, n), &size)) in layout.groups.iter().zip(&layout.sizes).enumerate() {
+            let mut squares = [Square(0); MAX_PIECES];
+            for (slot, &(_, sq)) in squares
+                .iter_mut()
+                .zip(stacks.iter().filter(|(c, _)| *c == code))
+            {
+                *slot = sq;
+            }
+            let squares = &mut squares[..n];
+            squares.sort_unstable_by_key(|sq| sq.0);
+
+            let rank = if i == 0 && layout.is_mirrored() {
+                LEFT_RANKS[squares[0].0 as usize].unwrap()
+            } else {
+                squares
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &sq)| BINOMIAL[free_rank(sq, used)][j + 1])
+                    .sum()
+            };
+
+            for sq in squares.iter() {
+                used |= 1 << sq.0;
+            }
+            idx = idx * size + rank;
+        }
+
+        pos.turn as usize * self.half + layout.offset + idx
+    }
+
+    // None past the end, every index below len() is a valid position
+    #[must_use]
+    pub fn position(&self, idx: usize) -> Option<Position> {
+        if idx >= self.len() {
+            return None;
+        }
+
+        let turn = match idx / self.half {
+            0 => Side::White,
+            _ => Side::Black,
+        };
+        let idx = idx % self.half;
+        let layout = self
+            .layouts
+            .iter()
+            .find(|layout| idx < layout.offset + layout.len)?;
+
+        let mut idx = idx - layout.offset;
+        let mut ranks = [0; MAX_PIECES];
+        for (i, &size) in layout.sizes.iter().enumerate().rev() {
+            ranks[i] = idx % size;
+            idx /= size;
+        }
+
+        let mut pos = Position::empty();
+        pos.turn = turn;
+        let mut used = 0u64;
+
+        for (i, &(code, n)) in layout.groups.iter().enumerate() {
+            let stack = Stack::from_code(code).unwrap();
+            let mut squares = [Square(0); MAX_PIECES];
+
+            if i == 0 && layout.is_mirrored() {
+                squares[0] = LEFT.0[ranks[0]];
+            } else {
+                let mut rank = ranks[i];
+                for j in (0..n).rev() {
+                    let mut r = j;
+                    while BINOMIAL[r + 1][j + 1] <= rank {
+                        r += 1;
+                    }
+                    rank -= BINOMIAL[r][j + 1];
+                    squares[j] = nth_free(used, r);
+                }
+            }
+
+            for &sq in &squares[..n] {
+                pos.set_stack(sq, stack);
+                used |= 1 << sq.0;
+            }
+        }
+
+        Some(pos)
+    }
+}
+
+impl Signature {
+    // None for signatures with more than MAX_PIECES pieces
+    #[must_use]
+    pub fn num_indices(&self) -> Option<usize> {
+        (self.num_pieces() <= MAX_PIECES).then(|| TableIndex::new(*self).len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        // Only sample the four piece tables
+        let tests = [
+            ("R vs R", 1),
+            ("RP vs S", 1),
+            ("WW vs P", 1),
+            ("RR vs SS", 97),
+            ("RPS vs W", 97),
+        ];
+
+        for (str, step) in tests {
+            let index = TableIndex::new(str.parse().unwrap());
+
+            for idx in (0..index.len()).step_by(step) {
+                let pos = index.position(idx).unwrap();
+                assert_eq!(pos.validate(), Ok(()));
+                assert_eq!(Signature::of(&pos), index.signature());
+                assert_eq!(index.index(&pos), idx);
+            }
+
+            assert_eq!(index.position(index.len()), None);
+        }
+    }
+
+    // A position and its mirror image share an index, unless the stack that
+    // decides stands in the middle column and they get one each
+    #[test]
+    fn mirrored() {
+        let index = TableIndex::new("RW vs SS".parse().unwrap());
+
+        for idx in (0..index.len()).step_by(97) {
+            let pos = index.position(idx).unwrap();
+
+            let mut mirrored = Position::empty();
+            mirrored.turn = pos.turn;
+            for sq in pos.get_occupied() {
+                mirrored.set_stack(sq.mirrored(), pos.stack_on(sq).unwrap());
+            }
+
+            let other = index.index(&mirrored);
+            assert!(other == idx || index.position(other) == Some(mirrored));
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn counts() {
+        // Two lone stacks, the first on the left half
+        let index = TableIndex::new("R vs R".parse().unwrap());
+        assert_eq!(LEFT.1, 24);
+        assert_eq!(index.len(), 2 * 24 * 44);
+
+        // The two rocks stand apart or make one of two stacks
+        let index = TableIndex::new("RR vs R".parse().unwrap());
+        assert_eq!(index.len(), 2 * (24 * 44 * 43 / 2 + 24 * 44));
+
+        // Nothing to mirror by when every stack has a twin
+        let index = TableIndex::new("RR vs SS".parse().unwrap());
+        let apart = 45 * 44 / 2 * (43 * 42 / 2);
+        let stacked = 24 * (44 * 43 / 2) + 24 * (44 * 43 / 2) + 24 * 44;
+        assert_eq!(index.len(), 2 * (apart + stacked));
+
+        // Wise can't sit on a rock
+        let index = TableIndex::new("RW vs S".parse().unwrap());
+        assert_eq!(index.len(), 2 * (24 * 44 * 43 + 24 * 44));
+    }
+}
@@ -0,0 +1,95 @@
+mod file;
+mod generate;
+mod index;
+mod probe;
+mod signature;
+
+pub use file::{TablebaseError, EXTENSION, VERSION};
+pub use generate::Generator;
+pub use index::TableIndex;
+pub use probe::Tablebases;
+pub use signature::{ParseSignatureError, Signature};
+
+use crate::{position::Position, result::MAX_HALFMOVES};
+
+// The most pieces a table can have, past this they don't fit in memory
+pub const MAX_PIECES: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+// From the side to move's point of view, wins and losses carry the number of
+// plies until the game ends with best play
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Draw,
+    Win(u8),
+    Loss(u8),
+}
+
+impl Outcome {
+    #[must_use]
+    pub fn wdl(&self) -> Wdl {
+        match self {
+            Outcome::Draw => Wdl::Draw,
+            Outcome::Win(_) => Wdl::Win,
+            Outcome::Loss(_) => Wdl::Loss,
+        }
+    }
+
+    #[must_use]
+    pub fn distance(&self) -> Option<u8> {
+        match self {
+            Outcome::Win(n) | Outcome::Loss(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    // Tables are solved without the halfmove counter. A result that can't be
+    // forced before the counter runs out is a draw, every other one stands.
+    #[must_use]
+    pub fn with_halfmoves(&self, halfmoves: i32) -> Self {
+        match self.distance() {
+            Some(n) if n > 0 && n as i32 > MAX_HALFMOVES - halfmoves => Outcome::Draw,
+            _ => *self,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Table {
+    index: TableIndex,
+    outcomes: Vec<Outcome>,
+}
+
+impl Table {
+    #[must_use]
+    pub fn signature(&self) -> Signature {
+        self.index.signature()
+    }
+
+    #[must_use]
+    pub fn index(&self) -> &TableIndex {
+        &self.index
+    }
+
+    #[must_use]
+    pub fn outcomes(&self) -> &[Outcome] {
+        &self.outcomes
+    }
+
+    // Respects the position's halfmove counter, None if the signature doesn't match
+    #[must_use]
+    pub fn probe(&self, pos: &Position) -> Option<Outcome> {
+        if Signature::of(pos) != self.signature() {
+            return None;
+        }
+
+        let outcome = self.outcomes[self.index.index(pos)];
+        Some(outcome.with_halfmoves(pos.halfmoves))
+    }
+}
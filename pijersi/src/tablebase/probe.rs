@@ -0,0 +1,266 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::position::Position;
+
+use super::{
+    file::{outcome_from_codes, read_stream, Header, TablebaseError, EXTENSION, HEADER_LEN},
+    signature::Signature,
+    Outcome, TableIndex, Wdl,
+};
+
+type Stream = OnceLock<Result<Vec<u8>, TablebaseError>>;
+
+struct Entry {
+    path: PathBuf,
+    header: Header,
+    index: TableIndex,
+    wdl: Stream,
+    dtw: Stream,
+}
+
+impl Entry {
+    fn load(&self, offset: u64, len: u64, checksum: u64) -> Result<Vec<u8>, TablebaseError> {
+        let mut file = BufReader::new(fs::File::open(&self.path)?);
+        file.seek(SeekFrom::Start(offset))?;
+        read_stream(&mut file, &self.header, len, checksum)
+    }
+
+    fn wdl(&self) -> Result<&[u8], TablebaseError> {
+        let header = &self.header;
+        self.wdl
+            .get_or_init(|| self.load(HEADER_LEN, header.wdl_len, header.wdl_checksum))
+            .as_deref()
+            .map_err(|err| *err)
+    }
+
+    fn dtw(&self) -> Result<&[u8], TablebaseError> {
+        let header = &self.header;
+        self.dtw
+            .get_or_init(|| {
+                self.load(
+                    HEADER_LEN + header.wdl_len,
+                    header.dtw_len,
+                    header.dtw_checksum,
+                )
+            })
+            .as_deref()
+            .map_err(|err| *err)
+    }
+}
+
+// A directory of table files. Only the headers are read up front, each
+// table's WDL and DTW streams are loaded and checked the first time they're
+// probed. Probes respect the position's halfmove counter.
+#[derive(Default)]
+pub struct Tablebases {
+    entries: HashMap<Signature, Entry>,
+}
+
+impl Tablebases {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, TablebaseError> {
+        let mut tablebases = Self::default();
+
+        for dir_entry in fs::read_dir(dir)? {
+            let path = dir_entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+
+            let mut file = fs::File::open(&path)?;
+            let header = Header::read(&mut file)?;
+
+            let expected = HEADER_LEN
+                .checked_add(header.wdl_len)
+                .and_then(|len| len.checked_add(header.dtw_len));
+            if Some(file.metadata()?.len()) != expected {
+                return Err(TablebaseError::Corrupt(header.signature));
+            }
+
+            // The file name has to agree with the contents
+            let name = path.file_name().and_then(|name| name.to_str());
+            if name != Some(header.signature.file_name().as_str()) {
+                let named = name
+                    .and_then(|name| name.strip_suffix(&format!(".{}", EXTENSION)))
+                    .and_then(|stem| stem.replacen('v', " vs ", 1).parse().ok())
+                    .unwrap_or_default();
+                return Err(TablebaseError::SignatureMismatch(named, header.signature));
+            }
+
+            tablebases.entries.insert(
+                header.signature,
+                Entry {
+                    path,
+                    header,
+                    index: TableIndex::new(header.signature),
+                    wdl: OnceLock::new(),
+                    dtw: OnceLock::new(),
+                },
+            );
+        }
+
+        Ok(tablebases)
+    }
+
+    pub fn signatures(&self) -> impl Iterator<Item = &Signature> {
+        self.entries.keys()
+    }
+
+    #[must_use]
+    pub fn contains(&self, signature: &Signature) -> bool {
+        self.entries.contains_key(signature)
+    }
+
+    fn entry(&self, pos: &Position) -> Result<(&Entry, usize), TablebaseError> {
+        let signature = Signature::of(pos);
+        let entry = self
+            .entries
+            .get(&signature)
+            .ok_or(TablebaseError::Missing(signature))?;
+        Ok((entry, entry.index.index(pos)))
+    }
+
+    fn outcome(&self, entry: &Entry, idx: usize, wdl: u8) -> Result<Outcome, TablebaseError> {
+        let dtw = entry.dtw()?[idx];
+        outcome_from_codes(wdl, dtw).ok_or(TablebaseError::Corrupt(entry.header.signature))
+    }
+
+    // Draws come straight from the WDL table, decisive results need the
+    // distance to check them against the halfmove counter
+    pub fn probe_wdl(&self, pos: &Position) -> Result<Wdl, TablebaseError> {
+        let (entry, idx) = self.entry(pos)?;
+
+        let wdl = entry.wdl()?[idx];
+        if outcome_from_codes(wdl, 0) == Some(Outcome::Draw) {
+            return Ok(Wdl::Draw);
+        }
+
+        let outcome = self.outcome(entry, idx, wdl)?;
+        Ok(outcome.with_halfmoves(pos.halfmoves).wdl())
+    }
+
+    // Plies until the game ends with best play, None if it's a draw
+    pub fn probe_dtw(&self, pos: &Position) -> Result<Option<u8>, TablebaseError> {
+        let (entry, idx) = self.entry(pos)?;
+        let wdl = entry.wdl()?[idx];
+        let outcome = self.outcome(entry, idx, wdl)?;
+        Ok(outcome.with_halfmoves(pos.halfmoves).distance())
+    }
+}
+
+#[cfg(all(test, not(any(feature = "small-board", feature = "large-board"))))]
+mod tests {
+    use super::*;
+    use crate::tablebase::{Generator, Table};
+    use std::io;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pijersi-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn generate(dir: &Path) -> Generator {
+        let mut generator = Generator::new();
+        generator.generate("R vs S".parse().unwrap());
+        for table in generator.tables() {
+            table.save(dir).unwrap();
+        }
+        generator
+    }
+
+    #[test]
+    fn probes() {
+        let dir = temp_dir("probes");
+        let generator = generate(&dir);
+        let tablebases = Tablebases::open(&dir).unwrap();
+        let table = generator.table(&"R vs S".parse().unwrap()).unwrap();
+        assert!(tablebases.contains(&table.signature()));
+
+        for idx in (0..table.index().len()).step_by(97) {
+            let mut pos = table.index().position(idx).unwrap();
+            for halfmoves in [0, 15, 20] {
+                pos.halfmoves = halfmoves;
+                let outcome = table.probe(&pos).unwrap();
+                assert_eq!(tablebases.probe_wdl(&pos), Ok(outcome.wdl()));
+                assert_eq!(tablebases.probe_dtw(&pos), Ok(outcome.distance()));
+            }
+        }
+
+        let pos = Position::from_fen("6/7/6/3R-r-2/6/7/6 w 0 1");
+        assert_eq!(
+            tablebases.probe_wdl(&pos),
+            Err(TablebaseError::Missing("R vs R".parse().unwrap()))
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt() {
+        let dir = temp_dir("corrupt");
+        generate(&dir);
+        let path = dir.join("RvS.pjtb");
+        let pos = Position::from_fen("6/R-6/6/3s-3/6/7/6 w 0 1");
+
+        // Damage in the tables only shows once they're loaded
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+
+        let tablebases = Tablebases::open(&dir).unwrap();
+        let signature = Signature::of(&pos);
+        assert_eq!(
+            tablebases.probe_dtw(&pos),
+            Err(TablebaseError::BadChecksum(signature))
+        );
+        assert_eq!(
+            tablebases.probe_wdl(&pos),
+            Err(TablebaseError::BadChecksum(signature))
+        );
+
+        // Truncated files are caught when opening
+        fs::write(&path, &bytes[..last]).unwrap();
+        assert_eq!(
+            Tablebases::open(&dir).err(),
+            Some(TablebaseError::Corrupt(signature))
+        );
+
+        // And lengths too big to add up
+        let mut huge = bytes.clone();
+        huge[22..30].fill(0xff);
+        huge[38..46].fill(0xff);
+        fs::write(&path, &huge).unwrap();
+        assert_eq!(
+            Tablebases::open(&dir).err(),
+            Some(TablebaseError::Corrupt(signature))
+        );
+
+        // So are files under the wrong name
+        let table: Table = Generator::new().generate(signature).clone();
+        table.save(&dir).unwrap();
+        fs::rename(&path, dir.join("SvR.pjtb")).unwrap();
+        assert_eq!(
+            Tablebases::open(&dir).err(),
+            Some(TablebaseError::SignatureMismatch(
+                "S vs R".parse().unwrap(),
+                signature
+            ))
+        );
+
+        fs::write(dir.join("SvR.pjtb"), b"not a table").unwrap();
+        assert_eq!(
+            Tablebases::open(&dir).err(),
+            Some(TablebaseError::Io(io::ErrorKind::UnexpectedEof))
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    position::{Piece, Position},
+    side::Side,
+    stack::piece_char,
+    validate::max_pieces,
+};
+
+const PIECES: [Piece; 4] = [Piece::Rock, Piece::Paper, Piece::Scissors, Piece::Wise];
+
+// Piece counts indexed by side then piece, written as eg. "RRPSW vs RPS"
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Signature {
+    pub counts: [[u8; 4]; 2],
+}
+
+impl Signature {
+    #[must_use]
+    pub fn of(pos: &Position) -> Self {
+        let mut signature = Self::default();
+        for side in [Side::White, Side::Black] {
+            let material = pos.material(side);
+            for piece in PIECES {
+                signature.counts[side as usize][piece as usize] = material.count(piece) as u8;
+            }
+        }
+        signature
+    }
+
+    #[must_use]
+    pub fn num_pieces(&self) -> usize {
+        self.counts.iter().flatten().map(|&n| n as usize).sum()
+    }
+
+    #[must_use]
+    pub fn has_pieces(&self, side: Side) -> bool {
+        self.counts[side as usize].iter().any(|&n| n > 0)
+    }
+
+    // Within the piece limits of a real game
+    #[must_use]
+    pub fn is_possible(&self) -> bool {
+        self.counts.iter().all(|counts| {
+            PIECES
+                .into_iter()
+                .all(|piece| counts[piece as usize] as i32 <= max_pieces(piece))
+        })
+    }
+
+    // Every piece, white before black, then R, P, S, W
+    #[must_use]
+    pub fn pieces(&self) -> Vec<(Side, Piece)> {
+        let mut pieces = vec![];
+        for side in [Side::White, Side::Black] {
+            for piece in PIECES {
+                for _ in 0..self.counts[side as usize][piece as usize] {
+                    pieces.push((side, piece));
+                }
+            }
+        }
+        pieces
+    }
+
+    // Signatures reachable by removing pieces while both sides keep at least one
+    #[must_use]
+    pub fn subsets(&self) -> Vec<Signature> {
+        let mut subsets = vec![Self::default()];
+
+        for side in 0..2 {
+            for piece in 0..4 {
+                subsets = subsets
+                    .into_iter()
+                    .flat_map(|sub| {
+                        (0..=self.counts[side][piece]).map(move |n| {
+                            let mut sub = sub;
+                            sub.counts[side][piece] = n;
+                            sub
+                        })
+                    })
+                    .collect();
+            }
+        }
+
+        subsets
+            .into_iter()
+            .filter(|sub| sub != self && sub.has_pieces(Side::White) && sub.has_pieces(Side::Black))
+            .collect()
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for side in [Side::White, Side::Black] {
+            if side == Side::Black {
+                write!(f, " vs ")?;
+            }
+            for piece in PIECES {
+                for _ in 0..self.counts[side as usize][piece as usize] {
+                    write!(f, "{}", piece_char(Side::White, piece))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParseSignatureError;
+
+impl fmt::Display for ParseSignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid material signature")
+    }
+}
+
+impl FromStr for Signature {
+    type Err = ParseSignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (white, black) = s.split_once(" vs ").ok_or(ParseSignatureError)?;
+        let mut signature = Self::default();
+
+        for (side, part) in [(Side::White, white), (Side::Black, black)] {
+            for c in part.trim().chars() {
+                let piece = match c.to_ascii_lowercase() {
+                    'r' => Piece::Rock,
+                    'p' => Piece::Paper,
+                    's' => Piece::Scissors,
+                    'w' => Piece::Wise,
+                    _ => return Err(ParseSignatureError),
+                };
+
+                let count = &mut signature.counts[side as usize][piece as usize];
+                *count += 1;
+                if *count as i32 > max_pieces(piece) {
+                    return Err(ParseSignatureError);
+                }
+            }
+        }
+
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings() {
+        let tests = ["R vs R", "RRPSW vs RPS", "WW vs P", " vs "];
+
+        for str in tests {
+            let signature = str.parse::<Signature>().unwrap();
+            assert_eq!(signature.to_string(), str);
+        }
+
+        assert_eq!(
+            "rp vs s".parse::<Signature>().unwrap().to_string(),
+            "RP vs S"
+        );
+
+        for str in ["", "R", "RvsR", "X vs R", "RRRRR vs R", "WWW vs R"] {
+            assert_eq!(str.parse::<Signature>(), Err(ParseSignatureError));
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn positions() {
+        let pos = Position::from_fen("s-4ww/7/6/7/6/7/RP4W- w 0 1");
+        let signature = Signature::of(&pos);
+
+        assert_eq!(signature.to_string(), pos.material_signature());
+        assert_eq!(signature.num_pieces(), 6);
+        assert_eq!(
+            signature.pieces(),
+            [
+                (Side::White, Piece::Rock),
+                (Side::White, Piece::Paper),
+                (Side::White, Piece::Wise),
+                (Side::Black, Piece::Scissors),
+                (Side::Black, Piece::Wise),
+                (Side::Black, Piece::Wise),
+            ]
+        );
+    }
+
+    #[test]
+    fn subsets() {
+        let signature = "RP vs SS".parse::<Signature>().unwrap();
+        let mut subsets: Vec<String> = signature
+            .subsets()
+            .iter()
+            .map(|sub| sub.to_string())
+            .collect();
+        subsets.sort();
+
+        assert_eq!(
+            subsets,
+            ["P vs S", "P vs SS", "R vs S", "R vs SS", "RP vs S"]
+        );
+    }
+}
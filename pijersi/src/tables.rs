@@ -0,0 +1,124 @@
+use crate::{
+    bitboard::Bitboard,
+    geometry::{NEIGHBOURS, NUM_SQUARES},
+    square::Square,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Jump {
+    // Has to be empty for the jump to be allowed
+    pub middle: Bitboard,
+    pub target: Bitboard,
+}
+
+const fn bit(sq: Option<Square>) -> Bitboard {
+    match sq {
+        Some(sq) => Bitboard(1u64 << sq.0),
+        None => Bitboard(0),
+    }
+}
+
+pub const ADJACENT: [Bitboard; NUM_SQUARES] = {
+    let mut table = [Bitboard(0); NUM_SQUARES];
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        let mut dir = 0;
+        while dir < 6 {
+            table[sq].0 |= bit(NEIGHBOURS[sq][dir]).0;
+            dir += 1;
+        }
+        sq += 1;
+    }
+    table
+};
+
+// Unused directions have an empty target so they never produce a move
+pub const JUMPS: [[Jump; 6]; NUM_SQUARES] = {
+    let empty = Jump {
+        middle: Bitboard(0),
+        target: Bitboard(0),
+    };
+    let mut table = [[empty; 6]; NUM_SQUARES];
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        let mut dir = 0;
+        while dir < 6 {
+            if let Some(middle) = NEIGHBOURS[sq][dir] {
+                if let Some(target) = NEIGHBOURS[middle.0 as usize][dir] {
+                    table[sq][dir] = Jump {
+                        middle: bit(Some(middle)),
+                        target: bit(Some(target)),
+                    };
+                }
+            }
+            dir += 1;
+        }
+        sq += 1;
+    }
+    table
+};
+
+pub const JUMP_TARGETS: [Bitboard; NUM_SQUARES] = {
+    let mut table = [Bitboard(0); NUM_SQUARES];
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        let mut dir = 0;
+        while dir < 6 {
+            table[sq].0 |= JUMPS[sq][dir].target.0;
+            dir += 1;
+        }
+        sq += 1;
+    }
+    table
+};
+
+#[must_use]
+pub fn adjacent(sq: Square) -> Bitboard {
+    ADJACENT[sq.0 as usize]
+}
+
+#[must_use]
+pub fn jumps(sq: Square, blockers: Bitboard) -> Bitboard {
+    let mut targets = JUMP_TARGETS[sq.0 as usize];
+
+    if (ADJACENT[sq.0 as usize] & blockers).is_occupied() {
+        for jump in &JUMPS[sq.0 as usize] {
+            if (jump.middle & blockers).is_occupied() {
+                targets ^= jump.target;
+            }
+        }
+    }
+
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{Rng, SplitMix64};
+
+    #[test]
+    fn adjacent_squares() {
+        for idx in 0..NUM_SQUARES as u8 {
+            let sq = Square(idx);
+            assert_eq!(adjacent(sq), Bitboard::from_square(&sq).adjacent());
+        }
+    }
+
+    #[test]
+    fn jump_squares() {
+        let mut rng = SplitMix64::new(0);
+
+        for idx in 0..NUM_SQUARES as u8 {
+            let sq = Square(idx);
+            let bb = Bitboard::from_square(&sq);
+
+            assert_eq!(JUMP_TARGETS[idx as usize], bb.doubles(Bitboard::empty()));
+
+            for _ in 0..100 {
+                let blockers = Bitboard(rng.next_u64()) & Bitboard::all();
+                assert_eq!(jumps(sq, blockers), bb.doubles(blockers));
+            }
+        }
+    }
+}
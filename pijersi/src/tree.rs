@@ -0,0 +1,281 @@
+// The move tree below a position, written out as Graphviz DOT or JSON for
+// looking at by hand.
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::{
+    mv::Mv,
+    position::Position,
+    result::{result_str, GameResult},
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TreeOptions {
+    pub depth: i32,
+    // Only the first this many legal moves of each node are expanded
+    pub max_children: Option<usize>,
+    // Positions reached more than one way share a node, found by Position::hash
+    // and then compared in full
+    pub merge: bool,
+}
+
+impl TreeOptions {
+    #[must_use]
+    pub fn new(depth: i32) -> Self {
+        Self {
+            depth,
+            max_children: None,
+            merge: false,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TreeNode {
+    pub fen: String,
+    // The move from the parent that first reached the node, None for the root
+    pub mv: Option<Mv>,
+    pub result: Option<GameResult>,
+    pub depth: i32,
+    // Moves with the index of the node they lead to
+    pub children: Vec<(Mv, usize)>,
+}
+
+// Node 0 is the root, nodes are numbered breadth first
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameTree {
+    pub nodes: Vec<TreeNode>,
+}
+
+impl GameTree {
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph tree {\n");
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let label = match node.result {
+                Some(result) => format!("{}\\n{}", node.fen, result_str(Some(result))),
+                None => node.fen.clone(),
+            };
+            dot += &format!("    n{} [label=\"{}\"];\n", idx, label);
+        }
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            for (mv, child) in &node.children {
+                dot += &format!("    n{} -> n{} [label=\"{}\"];\n", idx, child, mv);
+            }
+        }
+
+        dot += "}\n";
+        dot
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let quoted = |s: Option<String>| match s {
+            Some(s) => format!("\"{}\"", s),
+            None => "null".to_string(),
+        };
+
+        let nodes: Vec<String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| {
+                let children: Vec<String> = node
+                    .children
+                    .iter()
+                    .map(|(mv, child)| format!("{{\"move\":\"{}\",\"node\":{}}}", mv, child))
+                    .collect();
+
+                format!(
+                    "{{\"id\":{},\"fen\":\"{}\",\"move\":{},\"result\":{},\"depth\":{},\"children\":[{}]}}",
+                    idx,
+                    node.fen,
+                    quoted(node.mv.map(|mv| mv.to_string())),
+                    quoted(node.result.map(|result| result_str(Some(result)).to_string())),
+                    node.depth,
+                    children.join(",")
+                )
+            })
+            .collect();
+
+        format!("{{\"nodes\":[{}]}}\n", nodes.join(","))
+    }
+}
+
+fn new_node(pos: &Position, mv: Option<Mv>, depth: i32) -> TreeNode {
+    TreeNode {
+        fen: pos.get_fen(),
+        mv,
+        result: pos.result(),
+        depth,
+        children: Vec::new(),
+    }
+}
+
+impl Position {
+    #[must_use]
+    pub fn tree(&self, options: &TreeOptions) -> GameTree {
+        self.tree_hashed_by(options, Position::hash)
+    }
+
+    fn tree_hashed_by(&self, options: &TreeOptions, hash: fn(&Position) -> u64) -> GameTree {
+        let mut nodes = vec![new_node(self, None, 0)];
+        let mut positions = vec![*self];
+        let mut seen = BTreeMap::new();
+        seen.insert(hash(self), 0);
+
+        // Nodes are expanded in the order they're added, so breadth first
+        let mut idx = 0;
+        while idx < nodes.len() {
+            let pos = positions[idx];
+            let depth = nodes[idx].depth;
+            idx += 1;
+
+            if depth >= options.depth || pos.is_gameover() {
+                continue;
+            }
+
+            let limit = options.max_children.unwrap_or(usize::MAX);
+            for mv in pos.moves().take(limit) {
+                let npos = pos.after_move(&mv);
+                let key = hash(&npos);
+
+                // The hash only finds candidates, a collision mustn't join
+                // two different positions
+                let child = match seen.get(&key) {
+                    Some(&child) if options.merge && positions[child] == npos => child,
+                    _ => {
+                        seen.insert(key, nodes.len());
+                        nodes.push(new_node(&npos, Some(mv), depth + 1));
+                        positions.push(npos);
+                        nodes.len() - 1
+                    }
+                };
+                nodes[idx - 1].children.push((mv, child));
+            }
+        }
+
+        GameTree { nodes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn sizes() {
+        let pos = Position::from_fen("startpos");
+        let tests = [
+            (TreeOptions::new(0), 1),
+            (TreeOptions::new(2), 1 + 186 + 34054),
+            (
+                TreeOptions {
+                    max_children: Some(3),
+                    ..TreeOptions::new(2)
+                },
+                1 + 3 + 9,
+            ),
+        ];
+
+        for (options, size) in tests {
+            let tree = pos.tree(&options);
+            assert_eq!(tree.nodes.len(), size, "{:?}", options);
+
+            let edges: usize = tree.nodes.iter().map(|node| node.children.len()).sum();
+            assert_eq!(edges, size - 1);
+        }
+    }
+
+    #[test]
+    fn merged() {
+        let pos = Position::from_fen("startpos");
+        let tree = pos.tree(&TreeOptions {
+            merge: true,
+            ..TreeOptions::new(2)
+        });
+
+        // Each position is expanded once, with an edge for every move
+        assert!(tree.nodes.len() < 1 + 186 + 34054);
+
+        let mut fens: Vec<&str> = tree.nodes.iter().map(|node| node.fen.as_str()).collect();
+        fens.sort();
+        fens.dedup();
+        assert_eq!(fens.len(), tree.nodes.len());
+
+        for node in &tree.nodes {
+            let pos = Position::from_fen(&node.fen);
+            let moves = if node.depth < 2 {
+                pos.moves().count()
+            } else {
+                0
+            };
+            assert_eq!(node.children.len(), moves);
+
+            for (mv, child) in &node.children {
+                let npos = pos.after_move(mv);
+                assert_eq!(npos.get_fen(), tree.nodes[*child].fen);
+                assert_eq!(tree.nodes[*child].depth, node.depth + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn collisions() {
+        // Every position hashes the same, so only equal ones may share a node
+        let pos = Position::from_fen("startpos");
+        let options = TreeOptions {
+            max_children: Some(40),
+            merge: true,
+            ..TreeOptions::new(2)
+        };
+        let tree = pos.tree_hashed_by(&options, |_| 0);
+
+        for node in &tree.nodes {
+            let pos = Position::from_fen(&node.fen);
+            for (mv, child) in &node.children {
+                assert_eq!(pos.after_move(mv).get_fen(), tree.nodes[*child].fen);
+            }
+        }
+    }
+
+    #[cfg(not(any(feature = "small-board", feature = "large-board")))]
+    #[test]
+    fn output() {
+        let pos = Position::from_fen("6/6R-/r-5/7/6/7/6 w 0 1");
+        let tree = pos.tree(&TreeOptions::new(1));
+        assert_eq!(tree.nodes.len(), 4);
+        assert_eq!(tree.nodes[1].result, None);
+        assert_eq!(tree.nodes[3].result, Some(GameResult::WhiteWin));
+
+        let dot = tree.to_dot();
+        assert!(dot.starts_with("digraph tree {\n"));
+        assert!(dot.contains("    n0 [label=\"6/6R-/r-5/7/6/7/6 w 0 1\"];\n"));
+        assert!(dot.contains("    n3 [label=\"5R-/7/r-5/7/6/7/6 b 1 1\\nwhite\"];\n"));
+        assert!(dot.contains("    n0 -> n3 [label=\"f7g6\"];\n"));
+        assert!(dot.ends_with("}\n"));
+
+        let json = tree.to_json();
+        assert_eq!(
+            json,
+            concat!(
+                "{\"nodes\":[",
+                "{\"id\":0,\"fen\":\"6/6R-/r-5/7/6/7/6 w 0 1\",\"move\":null,\"result\":null,\"depth\":0,\"children\":[",
+                "{\"move\":\"f7e6\",\"node\":1},{\"move\":\"f7f6\",\"node\":2},{\"move\":\"f7g6\",\"node\":3}]},",
+                "{\"id\":1,\"fen\":\"6/7/r-4R-/7/6/7/6 b 1 1\",\"move\":\"f7e6\",\"result\":null,\"depth\":1,\"children\":[]},",
+                "{\"id\":2,\"fen\":\"6/5R-1/r-5/7/6/7/6 b 1 1\",\"move\":\"f7f6\",\"result\":null,\"depth\":1,\"children\":[]},",
+                "{\"id\":3,\"fen\":\"5R-/7/r-5/7/6/7/6 b 1 1\",\"move\":\"f7g6\",\"result\":\"white\",\"depth\":1,\"children\":[]}",
+                "]}\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use core::fmt;
+
+use crate::{
+    bitboard::Bitboard,
+    layer::Layer,
+    position::{Piece, Position},
+    side::Side,
+    square::Square,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PositionError {
+    OffBoard(Square),
+    SquareOccupied(Square),
+    SideOverlap(Square),
+    PieceOverlap(Square),
+    MissingSide(Square),
+    MissingPiece(Square),
+    FloatingPiece(Square),
+    WiseOnRps(Square),
+    TooManyPieces(Side, Piece),
+    BadHalfmoves(i32),
+    BadFullmoves(i32),
+    MailboxMismatch(Square),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::OffBoard(sq) => write!(f, "square {} is off the board", sq.0),
+            PositionError::SquareOccupied(sq) => write!(f, "square {} is already occupied", sq),
+            PositionError::SideOverlap(sq) => write!(f, "square {} belongs to both sides", sq),
+            PositionError::PieceOverlap(sq) => write!(f, "square {} has overlapping pieces", sq),
+            PositionError::MissingSide(sq) => write!(f, "square {} has pieces but no side", sq),
+            PositionError::MissingPiece(sq) => write!(f, "square {} has a side but no piece", sq),
+            PositionError::FloatingPiece(sq) => write!(f, "square {} has a floating piece", sq),
+            PositionError::WiseOnRps(sq) => write!(f, "square {} has wise on top of rps", sq),
+            PositionError::TooManyPieces(side, piece) => {
+                write!(f, "{} has too many {:?} pieces", side, piece)
+            }
+            PositionError::BadHalfmoves(n) => write!(f, "invalid halfmove counter {}", n),
+            PositionError::BadFullmoves(n) => write!(f, "invalid fullmove counter {}", n),
+            PositionError::MailboxMismatch(sq) => {
+                write!(f, "square {} disagrees with the mailbox", sq)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PositionError {}
+
+#[must_use]
+pub const fn max_pieces(piece: Piece) -> i32 {
+    match piece {
+        Piece::Rock | Piece::Paper | Piece::Scissors => 4,
+        Piece::Wise => 2,
+    }
+}
+
+impl Position {
+    // Like is_valid, but checked in every build mode and reporting what's wrong
+    pub fn validate(&self) -> Result<(), PositionError> {
+        let first = |bb: Bitboard| -> Result<(), Square> {
+            if bb.is_empty() {
+                Ok(())
+            } else {
+                Err(bb.lsb())
+            }
+        };
+
+        let white = self.sides[Side::White as usize];
+        let black = self.sides[Side::Black as usize];
+        let lower = self.get_lower();
+        let upper = self.get_upper();
+
+        let mut all = white | black;
+        for piece in &self.pieces {
+            all |= piece[Layer::Lower as usize] | piece[Layer::Upper as usize];
+        }
+        first(Bitboard(all.0 & !Bitboard::all().0)).map_err(PositionError::OffBoard)?;
+
+        first(white & black).map_err(PositionError::SideOverlap)?;
+
+        for layer in [Layer::Lower, Layer::Upper] {
+            let mut seen = Bitboard::empty();
+            for piece in &self.pieces {
+                first(seen & piece[layer as usize]).map_err(PositionError::PieceOverlap)?;
+                seen |= piece[layer as usize];
+            }
+        }
+
+        first(upper & !lower).map_err(PositionError::FloatingPiece)?;
+        first(lower & !(white | black)).map_err(PositionError::MissingSide)?;
+        first((white | black) & !lower).map_err(PositionError::MissingPiece)?;
+
+        let upper_wise = self.pieces[Piece::Wise as usize][Layer::Upper as usize];
+        let lower_wise = self.pieces[Piece::Wise as usize][Layer::Lower as usize];
+        first(upper_wise & !lower_wise).map_err(PositionError::WiseOnRps)?;
+
+        for side in [Side::White, Side::Black] {
+            for piece in [Piece::Rock, Piece::Paper, Piece::Scissors, Piece::Wise] {
+                let count = (self.pieces[piece as usize][Layer::Lower as usize]
+                    & self.sides[side as usize])
+                    .count()
+                    + (self.pieces[piece as usize][Layer::Upper as usize]
+                        & self.sides[side as usize])
+                        .count();
+
+                if count > max_pieces(piece) {
+                    return Err(PositionError::TooManyPieces(side, piece));
+                }
+            }
+        }
+
+        if self.halfmoves < 0 {
+            return Err(PositionError::BadHalfmoves(self.halfmoves));
+        }
+
+        if self.fullmoves < 1 {
+            return Err(PositionError::BadFullmoves(self.fullmoves));
+        }
+
+        if let Some(sq) = self.mailbox_mismatch() {
+            return Err(PositionError::MailboxMismatch(sq));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(any(feature = "small-board", feature = "large-board"))))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        let tests = [
+            "startpos",
+            "6/7/6/7/6/7/6 w 0 1",
+            "srp-r-s-2/p-1s-1r-1p-/4p-1/1RPw-3sr/2w-1RSP-/P-2WWS-2/R-S-S-1PR1 w 0 1",
+            "ww5/7/6/7/6/7/WR5 w 0 1",
+        ];
+
+        for fen in tests {
+            println!("{}", fen);
+            assert_eq!(Position::from_fen(fen).validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let sq = Square(3);
+        let bb = Bitboard::from_square(&sq);
+
+        let mut pos = Position::empty();
+        pos.sides[Side::White as usize] |= bb;
+        assert_eq!(pos.validate(), Err(PositionError::MissingPiece(sq)));
+
+        pos.pieces[Piece::Rock as usize][Layer::Upper as usize] |= bb;
+        assert_eq!(pos.validate(), Err(PositionError::FloatingPiece(sq)));
+
+        pos.pieces[Piece::Paper as usize][Layer::Lower as usize] |= bb;
+        assert_eq!(pos.validate(), Err(PositionError::MailboxMismatch(sq)));
+        pos.refresh_mailbox();
+        assert_eq!(pos.validate(), Ok(()));
+
+        pos.pieces[Piece::Rock as usize][Layer::Lower as usize] |= bb;
+        assert_eq!(pos.validate(), Err(PositionError::PieceOverlap(sq)));
+        pos.pieces[Piece::Rock as usize][Layer::Lower as usize] ^= bb;
+
+        pos.sides[Side::Black as usize] |= bb;
+        assert_eq!(pos.validate(), Err(PositionError::SideOverlap(sq)));
+        pos.sides[Side::Black as usize] ^= bb;
+
+        pos.pieces[Piece::Rock as usize][Layer::Upper as usize] ^= bb;
+        pos.pieces[Piece::Wise as usize][Layer::Upper as usize] |= bb;
+        assert_eq!(pos.validate(), Err(PositionError::WiseOnRps(sq)));
+
+        let mut pos = Position::empty();
+        pos.sides[Side::White as usize] = Bitboard(1 << 45);
+        assert_eq!(pos.validate(), Err(PositionError::OffBoard(Square(45))));
+
+        let pos = Position::from_fen("6/7/6/7/6/7/R-R-R-RR2 w 0 1");
+        assert_eq!(
+            pos.validate(),
+            Err(PositionError::TooManyPieces(Side::White, Piece::Rock))
+        );
+
+        let mut pos = Position::empty();
+        pos.halfmoves = -1;
+        assert_eq!(pos.validate(), Err(PositionError::BadHalfmoves(-1)));
+    }
+}
@@ -0,0 +1,70 @@
+// Nothing here assumes the standard board, so building with
+// --features small-board or large-board plays the same games on another shape
+#[cfg(test)]
+mod tests {
+    use pijersi::{
+        bitboard::Bitboard,
+        geometry::{NUM_SQUARES, SHAPE},
+        layer::Layer,
+        position::Position,
+        random::{Rng, SplitMix64},
+        shuffled_start::NUM_START_POSITIONS,
+        square::Square,
+    };
+
+    #[test]
+    fn startpos() {
+        let pos = Position::from_fen("startpos");
+        assert_eq!(pos.validate(), Ok(()));
+        assert_eq!(Bitboard::all().count() as usize, NUM_SQUARES);
+
+        let fen = pos.get_fen();
+        assert_eq!(fen.split('/').count(), SHAPE.rows as usize);
+        assert_eq!(Position::from_fen(&fen).get_fen(), fen);
+
+        // Black's setup is white's turned around
+        for idx in 0..NUM_SQUARES as u8 {
+            let sq = Square(idx);
+            let turned = Square(NUM_SQUARES as u8 - 1 - idx);
+            assert_eq!(
+                pos.get_side_on(sq).map(|side| !side),
+                pos.get_side_on(turned)
+            );
+            for layer in [Layer::Lower, Layer::Upper] {
+                assert_eq!(pos.get_piece_on(layer, sq), pos.get_piece_on(layer, turned));
+            }
+        }
+
+        let idx = pos.start_index().unwrap();
+        assert!(idx < NUM_START_POSITIONS);
+        assert_eq!(Position::from_start_index(idx).unwrap().get_fen(), fen);
+        assert!(!pos.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn games() {
+        let mut rng = SplitMix64::new(0);
+
+        for game in 0..20 {
+            let mut pos = if game == 0 {
+                Position::from_fen("startpos")
+            } else {
+                Position::shuffled_start(rng.next_u64())
+            };
+
+            while !pos.is_gameover() {
+                assert_eq!(pos.validate(), Ok(()), "{}", pos.get_fen());
+                assert_eq!(pos.count_moves_sneaky(), pos.count_moves());
+                assert!(pos.moves().eq(pos.legal_moves()));
+
+                let unpacked = Position::from_packed(&pos.to_packed()).unwrap();
+                assert_eq!(unpacked.get_fen(), pos.get_fen());
+
+                let moves = pos.legal_moves();
+                pos.makemove(&moves[rng.below(moves.len() as u32) as usize]);
+            }
+
+            assert!(pos.result().is_some());
+        }
+    }
+}
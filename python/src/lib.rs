@@ -0,0 +1,173 @@
+// Python module exposing the rules as pijersi.Position. See tests/test_pijersi.py
+// for how to build and import it.
+
+use pijersi::{
+    geometry::NUM_SQUARES,
+    layer::Layer,
+    position::{Piece, Position as Inner},
+    result::GameResult,
+    side::Side,
+};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+
+const PIECES: [Piece; 4] = [Piece::Rock, Piece::Paper, Piece::Scissors, Piece::Wise];
+const LAYERS: [Layer; 2] = [Layer::Lower, Layer::Upper];
+const SIDES: [Side; 2] = [Side::White, Side::Black];
+const NUM_PLANES: usize = PIECES.len() * LAYERS.len() + SIDES.len();
+
+#[pyclass(module = "pijersi")]
+#[derive(Clone)]
+pub struct Position {
+    inner: Inner,
+}
+
+impl Position {
+    fn parse(fen: &str) -> PyResult<Self> {
+        Inner::parse_fen(fen)
+            .map(|inner| Self { inner })
+            .map_err(|err| PyValueError::new_err(format!("invalid fen: {}", err)))
+    }
+
+    // One byte per square, planes ordered piece then layer: rock lower, rock
+    // upper, paper lower and so on, then white's and black's pieces. Bit i of
+    // a bitboard is square i.
+    #[must_use]
+    pub fn plane_bytes(&self) -> Vec<u8> {
+        let mut planes = Vec::with_capacity(NUM_PLANES * NUM_SQUARES);
+        for piece in PIECES {
+            for layer in LAYERS {
+                let bb = self.inner.pieces[piece as usize][layer as usize].0;
+                planes.extend((0..NUM_SQUARES).map(|sq| (bb >> sq & 1) as u8));
+            }
+        }
+        for side in SIDES {
+            let bb = self.inner.sides[side as usize].0;
+            planes.extend((0..NUM_SQUARES).map(|sq| (bb >> sq & 1) as u8));
+        }
+        planes
+    }
+}
+
+#[pymethods]
+impl Position {
+    #[new]
+    #[pyo3(signature = (fen = "startpos"))]
+    fn new(fen: &str) -> PyResult<Self> {
+        Self::parse(fen)
+    }
+
+    #[staticmethod]
+    fn from_fen(fen: &str) -> PyResult<Self> {
+        Self::parse(fen)
+    }
+
+    fn fen(&self) -> String {
+        self.inner.get_fen()
+    }
+
+    // "w" or "b"
+    #[getter]
+    fn turn(&self) -> &'static str {
+        match self.inner.turn {
+            Side::White => "w",
+            Side::Black => "b",
+        }
+    }
+
+    #[getter]
+    fn halfmoves(&self) -> i32 {
+        self.inner.halfmoves
+    }
+
+    #[getter]
+    fn fullmoves(&self) -> i32 {
+        self.inner.fullmoves
+    }
+
+    fn legal_moves(&self) -> Vec<String> {
+        self.inner.moves().map(|mv| mv.to_string()).collect()
+    }
+
+    fn push(&mut self, mv: &str) -> PyResult<()> {
+        match self.inner.moves().find(|legal| legal.to_string() == mv) {
+            Some(legal) => {
+                self.inner.makemove(&legal);
+                Ok(())
+            }
+            None => Err(PyValueError::new_err(format!("illegal move: {}", mv))),
+        }
+    }
+
+    // None while the game is going, otherwise "white", "black" or "draw"
+    fn result(&self) -> Option<&'static str> {
+        self.inner.result().map(|result| match result {
+            GameResult::WhiteWin => "white",
+            GameResult::BlackWin => "black",
+            GameResult::Draw => "draw",
+        })
+    }
+
+    fn perft(&self, py: Python<'_>, depth: i32) -> u64 {
+        py.allow_threads(|| self.inner.perft(depth))
+    }
+
+    // numpy.frombuffer(pos.planes(), dtype=numpy.uint8).reshape(Position.PLANES_SHAPE)
+    fn planes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.plane_bytes())
+    }
+
+    #[classattr]
+    #[allow(non_snake_case)]
+    fn PLANES_SHAPE() -> (usize, usize) {
+        (NUM_PLANES, NUM_SQUARES)
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.inner.get_fen() == other.inner.get_fen()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Position('{}')", self.inner.get_fen())
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "pijersi")]
+fn pijersi_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Position>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn planes() {
+        let pos = Position::parse("6/7/6/3R-rp2/6/7/6 w 0 1").unwrap();
+        let planes = pos.plane_bytes();
+        assert_eq!(planes.len(), NUM_PLANES * NUM_SQUARES);
+        assert_eq!(planes.iter().filter(|&&b| b == 1).count(), 5);
+
+        let tests = [(0, 22), (0, 23), (3, 23), (8, 22), (9, 23)];
+        for (plane, sq) in tests {
+            assert_eq!(planes[plane * NUM_SQUARES + sq], 1);
+        }
+    }
+
+    #[test]
+    fn bad_fens() {
+        let tests = ["", "not a fen", "6/7/6/7/6/7/6/6 w 0 1"];
+
+        for fen in tests {
+            assert!(Position::parse(fen).is_err(), "{}", fen);
+        }
+    }
+}
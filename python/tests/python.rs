@@ -0,0 +1,29 @@
+use std::{env, process::Command};
+
+// Runs the Python test script against the module cargo just built
+#[test]
+fn python() {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let name = format!(
+        "{}pijersi_python{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    );
+    let library = [deps.join(&name), deps.parent().unwrap().join(&name)]
+        .into_iter()
+        .find(|path| path.exists())
+        .expect("extension module not built");
+
+    let script = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test_pijersi.py");
+    let python = env::var("PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let output = Command::new(python)
+        .arg(script)
+        .env("PIJERSI_MODULE", library)
+        .output()
+        .expect("failed to run python");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
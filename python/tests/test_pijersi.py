@@ -0,0 +1,111 @@
+#!/usr/bin/env python3
+"""Builds the extension module and checks it from Python.
+
+    python3 python/tests/test_pijersi.py
+
+Set PIJERSI_MODULE to the path of an already built library to skip the
+cargo build, the Rust integration test in python/tests/python.rs does this.
+"""
+
+import importlib.machinery
+import importlib.util
+import json
+import os
+import subprocess
+import sys
+import unittest
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+LIBRARY = {
+    "darwin": "libpijersi_python.dylib",
+    "win32": "pijersi_python.dll",
+}.get(sys.platform, "libpijersi_python.so")
+
+
+def build():
+    subprocess.run(["cargo", "build", "-p", "pijersi-python"], cwd=HERE, check=True)
+    metadata = subprocess.run(
+        ["cargo", "metadata", "--format-version", "1", "--no-deps"],
+        cwd=HERE,
+        check=True,
+        capture_output=True,
+        text=True,
+    )
+    target = json.loads(metadata.stdout)["target_directory"]
+    return os.path.join(target, "debug", LIBRARY)
+
+
+def load(path):
+    # The library keeps cargo's file name, so load it under the module's name
+    loader = importlib.machinery.ExtensionFileLoader("pijersi", path)
+    spec = importlib.util.spec_from_file_location("pijersi", path, loader=loader)
+    module = importlib.util.module_from_spec(spec)
+    loader.exec_module(module)
+    return module
+
+
+pijersi = load(os.environ.get("PIJERSI_MODULE") or build())
+
+
+class PositionTests(unittest.TestCase):
+    def test_startpos(self):
+        pos = pijersi.Position()
+        self.assertEqual(pos, pijersi.Position.from_fen("startpos"))
+        self.assertEqual(pos.fen(), "s-p-r-s-p-r-/p-r-s-wwr-s-p-/6/7/6/P-S-R-WWS-R-P-/R-P-S-R-P-S- w 0 1")
+        self.assertEqual(pos.turn, "w")
+        self.assertIsNone(pos.result())
+
+    def test_fens(self):
+        fen = "6/7/6/3R-r-2/6/7/6 b 3 7"
+        pos = pijersi.Position.from_fen(fen)
+        self.assertEqual(pos.fen(), fen)
+        self.assertEqual(pos.halfmoves, 3)
+        self.assertEqual(pos.fullmoves, 7)
+        self.assertEqual(repr(pos), "Position('%s')" % fen)
+
+        for bad in ["", "not a fen", "6/7/6/7/6/7/6/6 w 0 1"]:
+            with self.assertRaises(ValueError):
+                pijersi.Position.from_fen(bad)
+
+    def test_perft(self):
+        pos = pijersi.Position()
+        for depth, nodes in enumerate([1, 186, 34054]):
+            self.assertEqual(pos.perft(depth), nodes)
+
+    def test_moves(self):
+        pos = pijersi.Position()
+        moves = pos.legal_moves()
+        self.assertEqual(len(moves), 186)
+        self.assertIn("b1c1", moves)
+
+        copy = pos.copy()
+        pos.push("b1c1")
+        self.assertEqual(pos.turn, "b")
+        self.assertNotEqual(pos, copy)
+        with self.assertRaises(ValueError):
+            pos.push("a1a1")
+
+    def test_results(self):
+        tests = [
+            ("6/7/6/3R-3/6/7/6 w 0 1", "white"),
+            ("6/7/6/3r-3/6/7/6 w 0 1", "black"),
+            ("6/7/6/3R-r-2/6/7/6 w 20 11", "draw"),
+        ]
+        for fen, result in tests:
+            self.assertEqual(pijersi.Position.from_fen(fen).result(), result)
+
+    def test_planes(self):
+        pos = pijersi.Position.from_fen("6/7/6/3R-rp2/6/7/6 w 0 1")
+        planes, squares = pijersi.Position.PLANES_SHAPE
+        data = pos.planes()
+        self.assertEqual(len(data), planes * squares)
+        self.assertEqual(sum(data), 5)
+        for plane, square in [(0, 22), (0, 23), (3, 23), (8, 22), (9, 23)]:
+            self.assertEqual(data[plane * squares + square], 1)
+
+        # The same bytes numpy.frombuffer would see
+        self.assertEqual(bytes(memoryview(data)), data)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -143,7 +143,7 @@ impl fmt::Display for GoSettings {
     }
 }
 
-pub fn parse(stream: &mut Peekable<SplitAsciiWhitespace>, mut func: impl FnMut(&GoSettings) -> ()) {
+pub fn parse(stream: &mut Peekable<SplitAsciiWhitespace>, mut func: impl FnMut(&GoSettings)) {
     let mut settings = GoSettings::default();
 
     while stream.peek().is_some() {
@@ -1,6 +1,6 @@
 use std::{iter::Peekable, str::SplitAsciiWhitespace};
 
-pub fn parse(stream: &mut Peekable<SplitAsciiWhitespace>, mut func: impl FnMut(&str) -> ()) {
+pub fn parse(stream: &mut Peekable<SplitAsciiWhitespace>, mut func: impl FnMut(&str)) {
     // Parse startpos/fen
     let fen = match stream.next() {
         Some("startpos") => "startpos".to_string(),
@@ -1,6 +1,6 @@
 use std::{iter::Peekable, str::SplitAsciiWhitespace};
 
-pub fn parse(stream: &mut Peekable<SplitAsciiWhitespace>, mut func: impl FnMut(&str, &str) -> ()) {
+pub fn parse(stream: &mut Peekable<SplitAsciiWhitespace>, mut func: impl FnMut(&str, &str)) {
     match stream.next() {
         Some("name") => {}
         _ => return,
@@ -15,10 +15,7 @@ pub fn parse(stream: &mut Peekable<SplitAsciiWhitespace>, mut func: impl FnMut(&
 
     let value = stream.next();
 
-    match (name, value) {
-        (Some(name), Some(value)) => {
-            func(name, value);
-        }
-        _ => {}
+    if let (Some(name), Some(value)) = (name, value) {
+        func(name, value);
     }
 }